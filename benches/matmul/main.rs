@@ -34,10 +34,10 @@ pub fn matmul2x2(c: &mut Criterion) {
     let mut group = c.benchmark_group("matmul 2x2");
 
     for mats in matss {
-        let mats = Align32::slice_align(&*mats);
+        let mats = Align32::slice_align(&mats);
         group.bench_with_input(BenchmarkId::new("avx", mats.len()), mats, |b, mats| {
             b.iter(|| {
-                let xs = avx::Matrix2x2::from_align_slice(&mats);
+                let xs = avx::Matrix2x2::from_align_slice(mats);
                 for tuple in xs.chunks(2) {
                     if let [a, b] = tuple {
                         let _c = *a * *b;
@@ -48,7 +48,7 @@ pub fn matmul2x2(c: &mut Criterion) {
         });
         group.bench_with_input(BenchmarkId::new("normal", mats.len()), mats, |b, mats| {
             b.iter(|| {
-                let xs = normal::Matrix2x2::from_slice(&mats);
+                let xs = normal::Matrix2x2::from_slice(mats);
                 for tuple in xs.chunks(2) {
                     if let [a, b] = tuple {
                         let _c = *a * *b;
@@ -67,10 +67,10 @@ fn matmul4x4(c: &mut Criterion) {
     let mut group = c.benchmark_group("matmul 4x4");
 
     for mats in matss {
-        let mats = Align32::slice_align(&*mats);
+        let mats = Align32::slice_align(&mats);
         group.bench_with_input(BenchmarkId::new("avx", mats.len()), mats, |b, mats| {
             b.iter(|| {
-                let xs = avx::Matrix4x4::from_align_slice(&mats);
+                let xs = avx::Matrix4x4::from_align_slice(mats);
                 for tuple in xs.chunks(2) {
                     if let [a, b] = tuple {
                         let _c = *a * *b;
@@ -81,7 +81,7 @@ fn matmul4x4(c: &mut Criterion) {
         });
         group.bench_with_input(BenchmarkId::new("normal", mats.len()), mats, |b, mats| {
             b.iter(|| {
-                let xs = normal::Matrix4x4::from_slice(&mats);
+                let xs = normal::Matrix4x4::from_slice(mats);
                 for tuple in xs.chunks(2) {
                     if let [a, b] = tuple {
                         let _c = *a * *b;
@@ -0,0 +1,83 @@
+use std::iter::repeat_with;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{thread_rng, Rng};
+#[cfg(feature = "avx512")]
+use simd_demo::merge::avx;
+use simd_demo::merge::{self, normal};
+
+pub fn merge_bench(c: &mut Criterion) {
+    let mut rand = thread_rng();
+
+    let mut group = c.benchmark_group("merge");
+
+    for count in [100, 10_000, 1_000_000] {
+        let mut a: Vec<i32> = repeat_with(|| rand.gen::<i32>()).take(count / 2).collect();
+        let mut b: Vec<i32> = repeat_with(|| rand.gen::<i32>()).take(count / 2).collect();
+        a.sort_unstable();
+        b.sort_unstable();
+        let mut out = vec![0; a.len() + b.len()];
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_function(BenchmarkId::new("simd", count), |bench| {
+                bench.iter(|| unsafe { avx::merge(&a, &b, &mut out) });
+            });
+        }
+
+        group.bench_function(BenchmarkId::new("dispatch", count), |bench| {
+            bench.iter(|| merge::merge(&a, &b, &mut out));
+        });
+
+        group.bench_function(BenchmarkId::new("normal", count), |bench| {
+            bench.iter(|| normal::merge(&a, &b, &mut out));
+        });
+    }
+
+    group.finish();
+}
+
+pub fn mergesort_bench(c: &mut Criterion) {
+    let mut rand = thread_rng();
+
+    let mut group = c.benchmark_group("mergesort");
+
+    for count in [10, 100, 1000, 10_000, 100_000, 1_000_000] {
+        let xs: Vec<i32> = repeat_with(|| rand.gen::<i32>()).take(count).collect();
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_with_input(BenchmarkId::new("simd", count), &xs, |b, xs| {
+                b.iter_batched(
+                    || xs.clone(),
+                    |mut xs| unsafe { avx::mergesort(&mut xs) },
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        group.bench_with_input(BenchmarkId::new("dispatch", count), &xs, |b, xs| {
+            b.iter_batched(
+                || xs.clone(),
+                |mut xs| merge::mergesort(&mut xs),
+                BatchSize::SmallInput,
+            );
+        });
+
+        group.bench_with_input(BenchmarkId::new("normal", count), &xs, |b, xs| {
+            b.iter_batched(
+                || xs.clone(),
+                |mut xs| normal::mergesort(&mut xs),
+                BatchSize::SmallInput,
+            );
+        });
+
+        group.bench_with_input(BenchmarkId::new("std", count), &xs, |b, xs| {
+            b.iter_batched(|| xs.clone(), |mut xs| xs.sort(), BatchSize::SmallInput);
+        });
+    }
+
+    group.finish();
+}
+criterion_group!(benches, merge_bench, mergesort_bench);
+criterion_main!(benches);
@@ -2,7 +2,9 @@ use std::iter::repeat_with;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use rand::{thread_rng, Rng};
-use simd_demo::num_parse::{avx, normal};
+#[cfg(feature = "avx512")]
+use simd_demo::num_parse::avx;
+use simd_demo::num_parse::{self, normal};
 
 pub fn num_parse_bench(c: &mut Criterion) {
     let mut rand = thread_rng();
@@ -14,10 +16,22 @@ pub fn num_parse_bench(c: &mut Criterion) {
         .take(1_000_000)
         .collect();
 
-    group.bench_function("simd", |b| {
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+        group.bench_function("simd", |b| {
+            b.iter(|| {
+                for s in &xs {
+                    let num = unsafe { avx::parse_u64(s) };
+                    black_box(num);
+                }
+            });
+        });
+    }
+
+    group.bench_function("dispatch", |b| {
         b.iter(|| {
             for s in &xs {
-                let num = avx::parse_u64(&s);
+                let num = num_parse::parse_u64(s);
                 black_box(num);
             }
         });
@@ -26,7 +40,7 @@ pub fn num_parse_bench(c: &mut Criterion) {
     group.bench_function("normal", |b| {
         b.iter(|| {
             for s in &xs {
-                let num = normal::parse_u64(&s);
+                let num = normal::parse_u64(s);
                 black_box(num);
             }
         });
@@ -43,6 +57,93 @@ pub fn num_parse_bench(c: &mut Criterion) {
 
     group.finish();
 
+    let mut group = c.benchmark_group("num parse i64");
+
+    let ys: Vec<String> = repeat_with(|| rand.gen::<i64>())
+        .map(|n| n.to_string())
+        .take(1_000_000)
+        .collect();
+
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+        group.bench_function("simd", |b| {
+            b.iter(|| {
+                for s in &ys {
+                    let num = unsafe { avx::parse_i64(s) };
+                    black_box(num);
+                }
+            });
+        });
+    }
+
+    group.bench_function("normal", |b| {
+        b.iter(|| {
+            for s in &ys {
+                let num = normal::parse_i64(s);
+                black_box(num);
+            }
+        });
+    });
+
+    group.bench_function("std", |b| {
+        b.iter(|| {
+            for s in &ys {
+                let num = s.parse::<i64>();
+                _ = black_box(num);
+            }
+        });
+    });
+
+    group.finish();
+
+    let mut group = c.benchmark_group("num parse u32");
+
+    let zs: Vec<String> = repeat_with(|| rand.gen::<u32>())
+        .map(|n| n.to_string())
+        .take(1_000_000)
+        .collect();
+
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+        group.bench_function("simd", |b| {
+            b.iter(|| {
+                for s in &zs {
+                    let num = unsafe { avx::parse_u32(s) };
+                    black_box(num);
+                }
+            });
+        });
+
+        group.bench_function("simd u64", |b| {
+            b.iter(|| {
+                for s in &zs {
+                    let num = unsafe { avx::parse_u64(s) };
+                    black_box(num);
+                }
+            });
+        });
+    }
+
+    group.bench_function("normal", |b| {
+        b.iter(|| {
+            for s in &zs {
+                let num = normal::parse_u32(s);
+                black_box(num);
+            }
+        });
+    });
+
+    group.bench_function("std", |b| {
+        b.iter(|| {
+            for s in &zs {
+                let num = s.parse::<u32>();
+                _ = black_box(num);
+            }
+        });
+    });
+
+    group.finish();
+
     let size: usize = xs.iter().map(String::len).sum();
     println!("{size}");
 }
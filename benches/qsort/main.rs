@@ -2,7 +2,11 @@ use std::iter::repeat_with;
 
 use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use rand::{thread_rng, Rng};
-use simd_demo::qsort::{avx, normal};
+#[cfg(feature = "avx512")]
+use simd_demo::qsort::avx;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use simd_demo::qsort::avx2;
+use simd_demo::qsort::{self, normal, radix};
 
 pub fn qsort_bench(c: &mut Criterion) {
     let mut rand = thread_rng();
@@ -12,10 +16,59 @@ pub fn qsort_bench(c: &mut Criterion) {
     for count in [10, 100, 1000, 10_000, 100_000, 1_000_000] {
         let xs: Vec<i32> = repeat_with(|| rand.gen::<i32>()).take(count).collect();
 
-        group.bench_with_input(BenchmarkId::new("simd", count), &xs, |b, xs| {
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_with_input(BenchmarkId::new("simd", count), &xs, |b, xs| {
+                b.iter_batched(
+                    || xs.clone(),
+                    |mut xs| unsafe { avx::qsort(&mut xs) },
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if is_x86_feature_detected!("avx2") {
+            group.bench_with_input(BenchmarkId::new("avx2", count), &xs, |b, xs| {
+                b.iter_batched(
+                    || xs.clone(),
+                    |mut xs| unsafe { avx2::qsort(&mut xs) },
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        group.bench_with_input(BenchmarkId::new("dispatch", count), &xs, |b, xs| {
+            b.iter_batched(
+                || xs.clone(),
+                |mut xs| qsort::sort(&mut xs),
+                BatchSize::SmallInput,
+            );
+        });
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_with_input(BenchmarkId::new("radix_simd", count), &xs, |b, xs| {
+                b.iter_batched(
+                    || xs.clone(),
+                    |mut xs| unsafe { radix::radix_sort_avx(&mut xs) },
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        group.bench_with_input(BenchmarkId::new("radix", count), &xs, |b, xs| {
             b.iter_batched(
                 || xs.clone(),
-                |mut xs| avx::qsort(&mut xs),
+                |mut xs| radix::radix_sort(&mut xs),
+                BatchSize::SmallInput,
+            );
+        });
+
+        group.bench_with_input(BenchmarkId::new("par", count), &xs, |b, xs| {
+            b.iter_batched(
+                || xs.clone(),
+                |mut xs| qsort::par_sort(&mut xs),
                 BatchSize::SmallInput,
             );
         });
@@ -39,5 +92,51 @@ pub fn qsort_bench(c: &mut Criterion) {
 
     group.finish();
 }
-criterion_group!(benches, qsort_bench);
+pub fn qsort_u64_bench(c: &mut Criterion) {
+    let mut rand = thread_rng();
+
+    let mut group = c.benchmark_group("qsort u64");
+
+    for count in [1000, 100_000, 1_000_000] {
+        let xs: Vec<u64> = repeat_with(|| rand.gen::<u64>()).take(count).collect();
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_with_input(BenchmarkId::new("simd", count), &xs, |b, xs| {
+                b.iter_batched(
+                    || xs.clone(),
+                    |mut xs| unsafe { avx::qsort(&mut xs) },
+                    BatchSize::SmallInput,
+                );
+            });
+
+            group.bench_with_input(BenchmarkId::new("radix_simd", count), &xs, |b, xs| {
+                b.iter_batched(
+                    || xs.clone(),
+                    |mut xs| unsafe { radix::radix_sort_avx(&mut xs) },
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        group.bench_with_input(BenchmarkId::new("radix", count), &xs, |b, xs| {
+            b.iter_batched(
+                || xs.clone(),
+                |mut xs| radix::radix_sort(&mut xs),
+                BatchSize::SmallInput,
+            );
+        });
+
+        group.bench_with_input(BenchmarkId::new("std", count), &xs, |b, xs| {
+            b.iter_batched(
+                || xs.clone(),
+                |mut xs| xs.sort_unstable(),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+criterion_group!(benches, qsort_bench, qsort_u64_bench);
 criterion_main!(benches);
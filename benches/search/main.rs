@@ -0,0 +1,60 @@
+use std::iter::repeat_with;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{thread_rng, Rng};
+#[cfg(feature = "avx512")]
+use simd_demo::search::avx;
+use simd_demo::search::{self, normal};
+
+pub fn search_bench(c: &mut Criterion) {
+    let mut rand = thread_rng();
+
+    let mut group = c.benchmark_group("lower_bound");
+
+    let keys: Vec<i32> = repeat_with(|| rand.gen::<i32>()).take(10_000).collect();
+
+    for count in [1000, 100_000, 10_000_000] {
+        let mut arr: Vec<i32> = repeat_with(|| rand.gen::<i32>()).take(count).collect();
+        arr.sort_unstable();
+        let mut out = vec![0; keys.len()];
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_function(BenchmarkId::new("simd", count), |b| {
+                b.iter(|| {
+                    for &key in &keys {
+                        black_box(unsafe { avx::lower_bound(&arr, key) });
+                    }
+                });
+            });
+
+            group.bench_function(BenchmarkId::new("simd batch", count), |b| {
+                b.iter(|| unsafe { avx::lower_bound_batch(&arr, &keys, &mut out) });
+            });
+        }
+
+        group.bench_function(BenchmarkId::new("dispatch batch", count), |b| {
+            b.iter(|| search::lower_bound_batch(&arr, &keys, &mut out));
+        });
+
+        group.bench_function(BenchmarkId::new("normal", count), |b| {
+            b.iter(|| {
+                for &key in &keys {
+                    black_box(normal::lower_bound(&arr, key));
+                }
+            });
+        });
+
+        group.bench_function(BenchmarkId::new("std", count), |b| {
+            b.iter(|| {
+                for &key in &keys {
+                    black_box(arr.binary_search(&key).unwrap_or_else(|i| i));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+criterion_group!(benches, search_bench);
+criterion_main!(benches);
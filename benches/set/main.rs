@@ -0,0 +1,84 @@
+use std::iter::repeat_with;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{thread_rng, Rng};
+#[cfg(feature = "avx512")]
+use simd_demo::set::avx;
+use simd_demo::set::{self, avx2, normal};
+
+pub fn set_bench(c: &mut Criterion) {
+    let mut rand = thread_rng();
+
+    let mut group = c.benchmark_group("intersect");
+
+    for count in [1000, 100_000, 1_000_000] {
+        let mut gen = || {
+            let mut arr: Vec<i32> = repeat_with(|| rand.gen_range(0..2 * count))
+                .take(count as usize)
+                .collect();
+            arr.sort_unstable();
+            arr.dedup();
+            arr
+        };
+        let (a, b) = (gen(), gen());
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_function(BenchmarkId::new("simd", count), |bench| {
+                bench.iter(|| unsafe { avx::intersect(&a, &b) });
+            });
+        }
+
+        if is_x86_feature_detected!("avx2") {
+            group.bench_function(BenchmarkId::new("avx2", count), |bench| {
+                bench.iter(|| unsafe { avx2::intersect(&a, &b) });
+            });
+        }
+
+        group.bench_function(BenchmarkId::new("dispatch", count), |bench| {
+            bench.iter(|| set::intersect(&a, &b));
+        });
+
+        group.bench_function(BenchmarkId::new("normal", count), |bench| {
+            bench.iter(|| normal::intersect(&a, &b));
+        });
+    }
+
+    group.finish();
+
+    let mut group = c.benchmark_group("dedup");
+
+    for count in [1000, 100_000, 1_000_000] {
+        let mut arr: Vec<i32> = repeat_with(|| rand.gen_range(0..count / 4))
+            .take(count as usize)
+            .collect();
+        arr.sort_unstable();
+
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            group.bench_function(BenchmarkId::new("simd", count), |bench| {
+                bench.iter_batched_ref(
+                    || arr.clone(),
+                    |arr| unsafe { avx::dedup(arr) },
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+
+        group.bench_function(BenchmarkId::new("normal", count), |bench| {
+            bench.iter_batched_ref(
+                || arr.clone(),
+                |arr| normal::dedup(arr),
+                BatchSize::LargeInput,
+            );
+        });
+
+        group.bench_function(BenchmarkId::new("std", count), |bench| {
+            bench.iter_batched_ref(|| arr.clone(), |arr| arr.dedup(), BatchSize::LargeInput);
+        });
+    }
+
+    group.finish();
+}
+criterion_group!(benches, set_bench);
+criterion_main!(benches);
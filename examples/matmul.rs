@@ -1,5 +1,5 @@
 use simd_demo::{
-    matrix::{avx, normal},
+    matrix::{self, avx, normal},
     Align32,
 };
 
@@ -45,4 +45,7 @@ fn main() {
     let z2 = x2 * y2;
 
     assert_eq!(*z1.as_array(), z2.0);
+
+    let z3 = matrix::matmul4x4(x1.as_array(), y1.as_array());
+    assert_eq!(z3, z2.0);
 }
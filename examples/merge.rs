@@ -0,0 +1,22 @@
+use std::array;
+
+use simd_demo::merge;
+
+fn main() {
+    let a: [_; 500] = array::from_fn(|i| i as i32 * 2);
+    let b: [_; 300] = array::from_fn(|i| i as i32 * 3);
+    let mut out = [0; 800];
+    merge::merge(&a, &b, &mut out);
+    works(&out);
+
+    let mut arr: [_; 1000] =
+        array::from_fn(|i| (i as f64 * f64::sin((i as f64) / 5.0)).ceil() as i32);
+    merge::mergesort(&mut arr);
+    works(&arr);
+}
+
+fn works<T: PartialOrd>(arr: &[T]) {
+    for (a, b) in arr.iter().zip(arr.iter().skip(1)) {
+        assert!(a <= b);
+    }
+}
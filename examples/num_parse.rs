@@ -1,21 +1,64 @@
-#![feature(stdarch_x86_avx512, slice_swap_unchecked)]
-
-use simd_demo::num_parse;
+use simd_demo::num_parse::{self, IntErrorKind};
 
 fn main() {
     let s = "123123123";
-    let num = num_parse::avx::parse_u64(s);
+    let num = num_parse::parse_u64(s);
     assert_eq!(num, Some(123123123));
 
     let s = "123123123a";
-    let num = num_parse::avx::parse_u64(s);
+    let num = num_parse::parse_u64(s);
     assert_eq!(num, None);
 
     let s = "18446744073709551616";
-    let num = num_parse::avx::parse_u64(s);
+    let num = num_parse::parse_u64(s);
     assert_eq!(num, None);
 
     let s = "18446744073709551615";
-    let num = num_parse::avx::parse_u64(s);
+    let num = num_parse::parse_u64(s);
     assert_eq!(num, Some(18446744073709551615));
+
+    assert_eq!(num_parse::parse_i64("-9223372036854775808"), Some(i64::MIN));
+    assert_eq!(num_parse::parse_i64("9223372036854775808"), None);
+    assert_eq!(num_parse::parse_i32("+2147483647"), Some(i32::MAX));
+    assert_eq!(num_parse::parse_i32("-2147483649"), None);
+    assert_eq!(num_parse::parse_i16("-32768"), Some(i16::MIN));
+    assert_eq!(num_parse::parse_i8("-"), None);
+    assert_eq!(num_parse::parse_i8("+-1"), None);
+
+    assert_eq!(num_parse::parse::<u8>("255"), Some(255));
+    assert_eq!(num_parse::parse::<u8>("256"), None);
+    assert_eq!(num_parse::parse::<u16>("8080"), Some(8080));
+    assert_eq!(num_parse::parse::<u32>("4294967295"), Some(u32::MAX));
+    assert_eq!(num_parse::parse::<u32>("4294967296"), None);
+    assert_eq!(
+        num_parse::parse::<u128>("340282366920938463463374607431768211455"),
+        Some(u128::MAX)
+    );
+    assert_eq!(
+        num_parse::parse::<u128>("340282366920938463463374607431768211456"),
+        None
+    );
+    assert_eq!(num_parse::parse::<i8>("-128"), Some(i8::MIN));
+    assert_eq!(num_parse::parse::<u32>("+7"), Some(7));
+    assert_eq!(num_parse::parse_u64(""), None);
+    assert_eq!(
+        num_parse::parse_u64("000000000000000000000000000042"),
+        Some(42)
+    );
+
+    assert_eq!(num_parse::try_parse::<u32>("42"), Ok(42));
+    let err = num_parse::try_parse::<u32>("12a4").unwrap_err();
+    assert_eq!(err.kind(), IntErrorKind::InvalidDigit);
+    assert_eq!(err.offset(), Some(2));
+    let err = num_parse::try_parse_i64("-9223372036854775809").unwrap_err();
+    assert_eq!(err.kind(), IntErrorKind::NegOverflow);
+    let err = num_parse::try_parse_u8("").unwrap_err();
+    assert_eq!(err.kind(), IntErrorKind::Empty);
+
+    let line = b"123,-45,abc";
+    let (a, len) = num_parse::parse_prefix::<u64>(line).unwrap();
+    assert_eq!((a, len), (123, 3));
+    let (b, len) = num_parse::parse_i64_prefix(&line[len + 1..]).unwrap();
+    assert_eq!((b, len), (-45, 3));
+    assert_eq!(num_parse::parse_u64_prefix(b"abc"), None);
 }
@@ -1,5 +1,3 @@
-#![feature(stdarch_x86_avx512, slice_swap_unchecked)]
-
 use std::array;
 
 use simd_demo::qsort;
@@ -7,11 +5,66 @@ use simd_demo::qsort;
 fn main() {
     let mut arr: [_; 1000] =
         array::from_fn(|i| (i as f64 * f64::sin((i as f64) / 5.0)).ceil() as i32);
-    qsort::avx::qsort(&mut arr);
+    qsort::sort(&mut arr);
+    works(&arr);
+    assert!(qsort::is_sorted(&arr));
+
+    arr.reverse();
+    assert!(!qsort::is_sorted(&arr));
+    qsort::sort(&mut arr);
+    works(&arr);
+
+    let mut arr: [_; 1000] = array::from_fn(|i| (i as u64).wrapping_mul(0x9E3779B97F4A7C15));
+    qsort::sort(&mut arr);
+    works(&arr);
+
+    let mut arr: [_; 1000] = array::from_fn(|i| i as f64 * f64::sin((i as f64) / 5.0));
+    qsort::sort(&mut arr);
+    works(&arr);
+
+    let mut arr: [_; 1000] = array::from_fn(|i| (i as i64).wrapping_mul(-0x61C8864680B583EB));
+    qsort::radix_sort(&mut arr);
+    works(&arr);
+
+    let mut arr: [_; 1000] = array::from_fn(|i| i as f64 * f64::sin((i as f64) / 5.0));
+    qsort::sort_desc(&mut arr);
+    arr.reverse();
     works(&arr);
+
+    let mut arr = [1.0, f64::NAN, 0.0, -0.0, f64::NEG_INFINITY, -1.0];
+    qsort::sort_total(&mut arr);
+    assert_eq!(
+        arr.map(f64::to_bits),
+        [f64::NEG_INFINITY, -1.0, -0.0, 0.0, 1.0, f64::NAN].map(f64::to_bits)
+    );
+
+    let mut arr: [_; 1000] = array::from_fn(|i| (i as i32 * 7919) % 1000);
+    let (_, nth, _) = qsort::select_nth_unstable(&mut arr, 500);
+    assert_eq!(*nth, 500);
+    qsort::partial_sort(&mut arr, 10);
+    assert_eq!(arr[..10], [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    let mut arr: [_; 1000] = array::from_fn(|i| (i as i32 * 7919) % 1000);
+    let n = qsort::partition_between(&mut arr, 100, 199);
+    assert_eq!(n, 100);
+    assert!(arr[..n].iter().all(|x| (100..=199).contains(x)));
+
+    let keys: [_; 1000] = array::from_fn(|i| (i as f64 * f64::sin((i as f64) / 5.0)).ceil() as i32);
+    let indices = qsort::argsort(&keys);
+    works(
+        &indices
+            .iter()
+            .map(|&i| keys[i as usize])
+            .collect::<Vec<_>>(),
+    );
+
+    let mut records: Vec<_> = keys.iter().map(|&k| (k, k.to_string())).collect();
+    qsort::sort_by_key(&mut records, |r| r.0);
+    works(&records.iter().map(|r| r.0).collect::<Vec<_>>());
+    assert!(records.iter().all(|r| r.0.to_string() == r.1));
 }
 
-fn works(arr: &[i32]) {
+fn works<T: PartialOrd>(arr: &[T]) {
     for (a, b) in arr.iter().zip(arr.iter().skip(1)) {
         assert!(a <= b);
     }
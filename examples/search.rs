@@ -0,0 +1,21 @@
+use std::array;
+
+use simd_demo::search;
+
+fn main() {
+    let arr: [_; 1000] = array::from_fn(|i| i as i32 / 3 * 2);
+
+    assert_eq!(search::lower_bound(&arr, 100), 150);
+    assert_eq!(search::upper_bound(&arr, 100), 153);
+    assert_eq!(search::equal_range(&arr, 100), 150..153);
+    assert!(search::contains(&arr, 664));
+    assert!(!search::contains(&arr, 101));
+    assert_eq!(search::lower_bound(&arr, i32::MAX), arr.len());
+
+    let keys: Vec<i32> = (-5..700).collect();
+    let mut out = vec![0; keys.len()];
+    search::lower_bound_batch(&arr, &keys, &mut out);
+    for (&key, &i) in keys.iter().zip(&out) {
+        assert_eq!(i, arr.partition_point(|&x| x < key));
+    }
+}
@@ -0,0 +1,30 @@
+use simd_demo::set;
+
+fn main() {
+    let a: Vec<i32> = (0..1000).step_by(2).collect();
+    let b: Vec<i32> = (0..1000).step_by(3).collect();
+
+    let intersect = set::intersect(&a, &b);
+    assert_eq!(intersect, (0..1000).step_by(6).collect::<Vec<_>>());
+    assert_eq!(set::intersect_count(&a, &b), intersect.len());
+
+    let union = set::union(&a, &b);
+    assert_eq!(
+        union,
+        (0..1000)
+            .filter(|x| x % 2 == 0 || x % 3 == 0)
+            .collect::<Vec<_>>()
+    );
+
+    let difference = set::difference(&a, &b);
+    assert_eq!(
+        difference,
+        (0..1000)
+            .filter(|x| x % 2 == 0 && x % 3 != 0)
+            .collect::<Vec<_>>()
+    );
+
+    let mut arr: Vec<u32> = (0..1000).map(|x| x / 7).collect();
+    let len = set::dedup(&mut arr);
+    assert_eq!(&arr[..len], (0..=999 / 7).collect::<Vec<_>>());
+}
@@ -0,0 +1,52 @@
+//! 对二进制文件中的定长整数记录做外部排序
+//!
+//! ```text
+//! external_sort <i32|u32|i64|u64> <input> <output> [--memory <MiB>] [--tmp-dir <dir>]
+//! ```
+
+use std::{env, path::Path, process};
+
+use simd_demo::qsort::{external_sort, ExternalSortConfig};
+
+const USAGE: &str =
+    "usage: external_sort <i32|u32|i64|u64> <input> <output> [--memory <MiB>] [--tmp-dir <dir>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 3 {
+        exit(USAGE);
+    }
+
+    let mut config = ExternalSortConfig::default();
+    let mut options = args[3..].iter();
+    while let Some(option) = options.next() {
+        let Some(value) = options.next() else {
+            exit(USAGE);
+        };
+        match option.as_str() {
+            "--memory" => match value.parse::<usize>() {
+                Ok(mib) => config.memory = mib << 20,
+                Err(_) => exit(&format!("invalid memory budget: {value}")),
+            },
+            "--tmp-dir" => config.tmp_dir = value.into(),
+            _ => exit(USAGE),
+        }
+    }
+
+    let (input, output) = (Path::new(&args[1]), Path::new(&args[2]));
+    let result = match args[0].as_str() {
+        "i32" => external_sort::<i32>(input, output, &config),
+        "u32" => external_sort::<u32>(input, output, &config),
+        "i64" => external_sort::<i64>(input, output, &config),
+        "u64" => external_sort::<u64>(input, output, &config),
+        _ => exit(USAGE),
+    };
+    if let Err(e) = result {
+        exit(&format!("external_sort: {e}"));
+    }
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1)
+}
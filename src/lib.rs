@@ -1,10 +1,13 @@
-#![feature(stdarch_x86_avx512, slice_swap_unchecked)]
+#![cfg_attr(feature = "nightly", feature(slice_swap_unchecked))]
 
 use std::ops::{Deref, DerefMut};
 
 pub mod matrix;
+pub mod merge;
 pub mod num_parse;
 pub mod qsort;
+pub mod search;
+pub mod set;
 
 #[repr(align(32))]
 pub struct Align32<T: ?Sized>(pub T);
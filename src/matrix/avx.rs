@@ -313,3 +313,19 @@ impl DerefMut for Vector4 {
         unsafe { &mut *(self as *mut Self as *mut _) }
     }
 }
+
+/// # Safety
+/// - CPU需要支持 `avx2` 和 `fma`
+#[inline]
+#[target_feature(enable = "avx2,fma")]
+pub fn matmul2x2(a: &[[f64; 2]; 2], b: &[[f64; 2]; 2]) -> [[f64; 2]; 2] {
+    (Matrix2x2::new(*a) * Matrix2x2::new(*b)).into()
+}
+
+/// # Safety
+/// - CPU需要支持 `avx2` 和 `fma`
+#[inline]
+#[target_feature(enable = "avx2,fma")]
+pub fn matmul4x4(a: &[[f64; 4]; 4], b: &[[f64; 4]; 4]) -> [[f64; 4]; 4] {
+    *(Matrix4x4::new(Align32(*a)) * Matrix4x4::new(Align32(*b))).as_array()
+}
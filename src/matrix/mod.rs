@@ -1,8 +1,41 @@
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx2",
-    target_feature = "fma",
-))]
+use std::sync::OnceLock;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub mod avx;
 
 pub mod normal;
+
+type Mat2 = [[f64; 2]; 2];
+type Mat4 = [[f64; 4]; 4];
+
+/// 运行时检测CPU特性，选择最快的实现做2x2矩阵乘法（检测结果只做一次）
+pub fn matmul2x2(a: &Mat2, b: &Mat2) -> Mat2 {
+    static MATMUL2X2: OnceLock<fn(&Mat2, &Mat2) -> Mat2> = OnceLock::new();
+
+    let matmul = MATMUL2X2.get_or_init(|| {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return |a, b| unsafe { avx::matmul2x2(a, b) };
+        }
+
+        normal::matmul2x2
+    });
+
+    matmul(a, b)
+}
+
+/// 运行时检测CPU特性，选择最快的实现做4x4矩阵乘法（检测结果只做一次）
+pub fn matmul4x4(a: &Mat4, b: &Mat4) -> Mat4 {
+    static MATMUL4X4: OnceLock<fn(&Mat4, &Mat4) -> Mat4> = OnceLock::new();
+
+    let matmul = MATMUL4X4.get_or_init(|| {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return |a, b| unsafe { avx::matmul4x4(a, b) };
+        }
+
+        normal::matmul4x4
+    });
+
+    matmul(a, b)
+}
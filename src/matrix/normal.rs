@@ -205,3 +205,13 @@ impl Mul for Matrix4x4 {
         Self(d)
     }
 }
+
+#[inline]
+pub fn matmul2x2(a: &[[f64; 2]; 2], b: &[[f64; 2]; 2]) -> [[f64; 2]; 2] {
+    (Matrix2x2::new(*a) * Matrix2x2::new(*b)).into()
+}
+
+#[inline]
+pub fn matmul4x4(a: &[[f64; 4]; 4], b: &[[f64; 4]; 4]) -> [[f64; 4]; 4] {
+    (Matrix4x4(*a) * Matrix4x4(*b)).0
+}
@@ -0,0 +1,113 @@
+use super::mergesort_with;
+use crate::qsort::avx::{
+    bitonic_merge_impl, bitonic_sort_small, SimdElement, DEFAULT_BITONIC_VECTORS,
+};
+
+/// 先用双调排序网络排好的段长
+const RUN: usize = DEFAULT_BITONIC_VECTORS * 16;
+
+/// 把有序的`a`和`b`合并到`out`
+///
+/// 两边各取一个向量，用双调合并网络得到最小的16个输出；剩下的16个留在寄存器里，
+/// 与下一个向量（从首元素较小的一边取）继续合并。不满一个向量时用 `i32::MAX` 填充。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `out.len() != a.len() + b.len()`
+#[target_feature(enable = "avx512f")]
+pub fn merge(a: &[i32], b: &[i32], out: &mut [i32]) {
+    assert_eq!(out.len(), a.len() + b.len());
+    unsafe { merge_impl(a, b, out) }
+}
+
+/// 每次合并`T::LANES`个分量，不满一个向量时用`T::PADDING`填充
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+/// - out.len() == a.len() + b.len()
+/// - `a`、`b`中没有NaN
+#[inline]
+#[target_feature(enable = "avx512f")]
+pub(crate) unsafe fn merge_impl<T: SimdElement>(a: &[T], b: &[T], out: &mut [T]) {
+    let s = T::LANES;
+    if a.is_empty() || b.is_empty() {
+        out[..a.len()].copy_from_slice(a);
+        out[a.len()..].copy_from_slice(b);
+        return;
+    }
+
+    let (mut i, mut j) = (0, 0);
+    unsafe {
+        let mut inputs = [load_padded(a, &mut i), load_padded(b, &mut j)];
+        bitonic_merge_impl::<T, [T::Vector]>(&mut inputs, 2);
+        store_truncated(out, 0, inputs[0]);
+
+        for k in (s..out.len()).step_by(s) {
+            // 下一个向量来自首元素较小的一边，合并后的较小一半就是接下来的`s`个输出
+            let next = if j >= b.len() || (i < a.len() && a[i] <= b[j]) {
+                load_padded(a, &mut i)
+            } else {
+                load_padded(b, &mut j)
+            };
+            inputs = [inputs[1], next];
+            bitonic_merge_impl::<T, [T::Vector]>(&mut inputs, 2);
+            store_truncated(out, k, inputs[0]);
+        }
+    }
+}
+
+/// 归并排序，需要与`arr`等长的缓冲区
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn mergesort(arr: &mut [i32]) {
+    mergesort_with(
+        arr,
+        RUN,
+        |run| {
+            if run.len() >= 2 {
+                unsafe { bitonic_sort_small(run) }
+            }
+        },
+        |a, b, out| merge(a, b, out),
+    );
+}
+
+/// 读入`v[*i..]`的（最多）一个向量，不满的部分用`T::PADDING`填充；`v`读完后返回全是`T::PADDING`的向量
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn load_padded<T: SimdElement>(v: &[T], i: &mut usize) -> T::Vector {
+    let s = T::LANES;
+    let rest = v.len().saturating_sub(*i);
+    unsafe {
+        let ptr = v.as_ptr().add((*i).min(v.len()));
+        let vec = if rest >= s {
+            T::loadu(ptr)
+        } else {
+            T::mask_loadu(T::set1(T::PADDING), !(!0 << rest), ptr)
+        };
+        *i += rest.min(s);
+        vec
+    }
+}
+
+/// 把`vec`写到`out[k..]`，超出`out`的分量丢掉
+///
+/// # Safety
+/// - k < out.len()
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn store_truncated<T: SimdElement>(out: &mut [T], k: usize, vec: T::Vector) {
+    let rest = out.len() - k;
+    unsafe {
+        let ptr = out.as_mut_ptr().add(k);
+        if rest >= T::LANES {
+            T::storeu(ptr, vec);
+        } else {
+            T::mask_compressstoreu(ptr, !(!0 << rest), vec);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::OnceLock;
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+/// 用双调合并网络每次合并16个分量
+pub mod avx;
+pub mod normal;
+
+/// 把有序的`a`和`b`合并到`out`，运行时检测CPU特性，选择最快的实现（检测结果只做一次）
+///
+/// # Panics
+/// - `out.len() != a.len() + b.len()`
+pub fn merge(a: &[i32], b: &[i32], out: &mut [i32]) {
+    type Merge = fn(&[i32], &[i32], &mut [i32]);
+    static MERGE: OnceLock<Merge> = OnceLock::new();
+
+    let merge = MERGE.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |a, b, out| unsafe { avx::merge(a, b, out) };
+        }
+
+        normal::merge
+    });
+
+    merge(a, b, out)
+}
+
+/// 归并排序，运行时选择最快的实现
+pub fn mergesort(arr: &mut [i32]) {
+    static MERGESORT: OnceLock<fn(&mut [i32])> = OnceLock::new();
+
+    let mergesort = MERGESORT.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |arr| unsafe { avx::mergesort(arr) };
+        }
+
+        normal::mergesort
+    });
+
+    mergesort(arr)
+}
+
+/// 自底向上的归并排序：先用`sort_run`把每`run`个元素排好序，
+/// 再用`merge`逐层两两合并，在`arr`和同样长的缓冲区之间来回倒，结果放回`arr`
+fn mergesort_with(
+    arr: &mut [i32],
+    run: usize,
+    sort_run: impl Fn(&mut [i32]),
+    merge: impl Fn(&[i32], &[i32], &mut [i32]),
+) {
+    let len = arr.len();
+    for chunk in arr.chunks_mut(run) {
+        sort_run(chunk);
+    }
+    if len <= run {
+        return;
+    }
+
+    let mut buf = vec![0; len];
+    let mut in_arr = true;
+    let mut width = run;
+    while width < len {
+        let (src, dst): (&[i32], &mut [i32]) = if in_arr {
+            (&*arr, &mut buf)
+        } else {
+            (&buf, &mut *arr)
+        };
+        for (start, out) in (0..len).step_by(2 * width).zip(dst.chunks_mut(2 * width)) {
+            let mid = (start + width).min(len);
+            merge(&src[start..mid], &src[mid..start + out.len()], out);
+        }
+        in_arr = !in_arr;
+        width *= 2;
+    }
+    if !in_arr {
+        arr.copy_from_slice(&buf);
+    }
+}
@@ -0,0 +1,40 @@
+use super::mergesort_with;
+
+/// 先用插入排序排好的段长
+const RUN: usize = 32;
+
+/// 把有序的`a`和`b`合并到`out`，相等时`a`中的元素在前
+///
+/// # Panics
+/// - `out.len() != a.len() + b.len()`
+pub fn merge(a: &[i32], b: &[i32], out: &mut [i32]) {
+    assert_eq!(out.len(), a.len() + b.len());
+
+    let (mut i, mut j) = (0, 0);
+    for x in out.iter_mut() {
+        *x = if j >= b.len() || (i < a.len() && a[i] <= b[j]) {
+            i += 1;
+            a[i - 1]
+        } else {
+            j += 1;
+            b[j - 1]
+        };
+    }
+}
+
+/// 归并排序，需要与`arr`等长的缓冲区
+pub fn mergesort(arr: &mut [i32]) {
+    mergesort_with(arr, RUN, insertion_sort, merge);
+}
+
+fn insertion_sort(v: &mut [i32]) {
+    for i in 1..v.len() {
+        let x = v[i];
+        let mut j = i;
+        while j > 0 && v[j - 1] > x {
+            v[j] = v[j - 1];
+            j -= 1;
+        }
+        v[j] = x;
+    }
+}
@@ -1,36 +1,166 @@
+#[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::{
-    _mm256_cvtepi16_epi8, _mm256_maddubs_epi16, _mm256_mask_cmpgt_epu8_mask,
+    __m128i, _mm256_cvtepi16_epi8, _mm256_maddubs_epi16, _mm256_mask_cmpgt_epu8_mask,
     _mm256_maskz_loadu_epi8, _mm256_maskz_sub_epi8, _mm256_set1_epi8, _mm256_set_epi8,
-    _mm_extract_epi32, _mm_madd_epi16, _mm_maddubs_epi16, _mm_set_epi16, _mm_set_epi8,
+    _mm_cvtsi128_si32, _mm_extract_epi32, _mm_madd_epi16, _mm_maddubs_epi16,
+    _mm_mask_cmpgt_epu8_mask, _mm_maskz_loadu_epi8, _mm_maskz_sub_epi8, _mm_packus_epi32,
+    _mm_set1_epi8, _mm_set_epi16, _mm_set_epi8,
 };
 
-#[inline(always)]
-pub fn parse_u64(s: &str) -> Option<u64> {
-    let bytes = s.as_bytes();
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m128i, _mm256_cvtepi16_epi8, _mm256_maddubs_epi16, _mm256_mask_cmpgt_epu8_mask,
+    _mm256_maskz_loadu_epi8, _mm256_maskz_sub_epi8, _mm256_set1_epi8, _mm256_set_epi8,
+    _mm_cvtsi128_si32, _mm_extract_epi32, _mm_madd_epi16, _mm_maddubs_epi16,
+    _mm_mask_cmpgt_epu8_mask, _mm_maskz_loadu_epi8, _mm_maskz_sub_epi8, _mm_packus_epi32,
+    _mm_set1_epi8, _mm_set_epi16, _mm_set_epi8,
+};
+
+use super::{apply_sign, parse_error, split_sign, strip_plus, unsigned_digits, ParseIntError};
 
-    // 1. 超过20字节的字符串超过u64的范围
+/// 只有数字的字符串，不能为空
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn u64_digits(bytes: &[u8]) -> Option<u64> {
+    let bytes = skip_leading_zeros(bytes, 20);
+
+    // 1. 去掉前导零后超过20字节的字符串超过u64的范围
     if bytes.len() > 20 {
         return None;
     }
 
-    let start = bytes.as_ptr();
-    let end = unsafe { start.offset(bytes.len() as isize) };
-    let mask = 0xFFFFFFFF_u32 << (32 - s.len());
+    let base10e8_32bit = fold32(bytes)?;
+
+    // 使用标量计算剩余数的十进制求和
+    //
+    // `u64::MAX == 1844_67440737_09551615`(20bytes)
+    // 向量只看高位3个分量
+    let res_1digit = _mm_extract_epi32(base10e8_32bit, 3) as u64;
+    if bytes.len() <= 8 {
+        return Some(res_1digit);
+    }
+
+    let middle_part = _mm_extract_epi32(base10e8_32bit, 2) as u64;
+    let res_2digit = res_1digit + 1_0000_0000 * middle_part;
+    if bytes.len() <= 16 {
+        return Some(res_2digit);
+    }
+
+    let high_part = _mm_extract_epi32(base10e8_32bit, 1) as u64;
+    if high_part > 1844 || (high_part == 1844 && res_2digit > 67440737_09551615) {
+        None
+    } else {
+        Some(res_2digit + 1_0000_0000_0000_0000 * high_part)
+    }
+}
+
+/// 最多10位数字，只用128位向量
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn u32_digits(bytes: &[u8]) -> Option<u32> {
+    let bytes = skip_leading_zeros(bytes, 10);
+    if bytes.len() > 10 {
+        return None;
+    }
+
+    // `u32::MAX == 42_94967295`
+    let (high, low) = fold16(bytes)?;
+    if high > 42 || (high == 42 && low > 94967295) {
+        None
+    } else {
+        Some(high * 1_0000_0000 + low)
+    }
+}
+
+/// 最多5位数字，只用128位向量的低8位一组
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn u16_digits(bytes: &[u8]) -> Option<u16> {
+    let bytes = skip_leading_zeros(bytes, 5);
+    if bytes.len() > 5 {
+        return None;
+    }
+
+    let (_, low) = fold16(bytes)?;
+    u16::try_from(low).ok()
+}
+
+/// 最多3位数字，只用128位向量的低8位一组
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn u8_digits(bytes: &[u8]) -> Option<u8> {
+    let bytes = skip_leading_zeros(bytes, 3);
+    if bytes.len() > 3 {
+        return None;
+    }
+
+    let (_, low) = fold16(bytes)?;
+    u8::try_from(low).ok()
+}
+
+/// 最多39位数字：低32位数字用256位向量折叠，剩下最多7位用128位向量折叠
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn u128_digits(bytes: &[u8]) -> Option<u128> {
+    let bytes = skip_leading_zeros(bytes, 39);
+    if bytes.len() > 39 {
+        return None;
+    }
+
+    let (high, low) = bytes.split_at(bytes.len().saturating_sub(32));
+    let base10e8_32bit = fold32(low)?;
+    let [p0, p1, p2, p3] = [
+        _mm_extract_epi32(base10e8_32bit, 0) as u32,
+        _mm_extract_epi32(base10e8_32bit, 1) as u32,
+        _mm_extract_epi32(base10e8_32bit, 2) as u32,
+        _mm_extract_epi32(base10e8_32bit, 3) as u32,
+    ];
+    let low = ((p0 as u128 * 1_0000_0000 + p1 as u128) * 1_0000_0000 + p2 as u128) * 1_0000_0000
+        + p3 as u128;
+    if high.is_empty() {
+        return Some(low);
+    }
+
+    let (_, high) = fold16(high)?;
+    (high as u128)
+        .checked_mul(10_u128.pow(32))?
+        .checked_add(low)
+}
+
+/// 比`max_len`长时去掉开头的`0`，让前导零多的数字串也能放进向量
+#[inline]
+fn skip_leading_zeros(bytes: &[u8], max_len: usize) -> &[u8] {
+    if bytes.len() <= max_len {
+        return bytes;
+    }
+    let zeros = bytes.iter().take_while(|&&b| b == b'0').count();
+    &bytes[zeros..]
+}
+
+/// 把最多32字节的数字串折叠成4组8位数字（4x32bits），第0个分量是最高的一组；有非数字的字节时返回 `None`
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn fold32(bytes: &[u8]) -> Option<__m128i> {
+    let end = bytes.as_ptr_range().end;
+    // 高`len`位为1；用右移而不是 `<< (32 - len)`，避免编译出8位的减法，
+    // 部分寄存器写入会让相邻两次解析串成依赖链
+    let mask = (0xFFFFFFFF_00000000_u64 >> bytes.len()) as u32;
 
     let base10_8bit = unsafe {
         let ascii_zero = _mm256_set1_epi8('0' as i8);
         let nine = _mm256_set1_epi8(9);
 
-        // 2. 将字符串读入到向量的高位中，向量低位置为0（最少浪费12位）
+        // 1. 将字符串读入到向量的高位中，向量低位置为0
         //    *注意*：
         //    - `__m256i` 可以表示32个 `u8`
         //    - 字符串的高低位与数字的高低位是相反的
-        let s_bytes_v = _mm256_maskz_loadu_epi8(mask, end.offset(-32).cast());
+        //    - 掩码为0的字节不会被读取
+        let s_bytes_v = _mm256_maskz_loadu_epi8(mask, end.wrapping_sub(32).cast());
 
-        // 3. 将向量读到的字符串部分，每个字节 - '0'
+        // 2. 将向量读到的字符串部分，每个字节 - '0'
         let base10_8bit = _mm256_maskz_sub_epi8(mask, s_bytes_v, ascii_zero);
 
-        // 4. 如果存在字节 > 9的，说明存在非数字的字节
+        // 3. 如果存在字节 > 9的，说明存在非数字的字节
         //    *注意*: 这里按u8解释i8，所以如果是负数，也会 > 9
         let nondigits = _mm256_mask_cmpgt_epu8_mask(mask, base10_8bit, nine);
         if nondigits != 0 {
@@ -42,10 +172,7 @@ pub fn parse_u64(s: &str) -> Option<u64> {
 
     // 使用向量计算10进制求和
     // 最后得到8位(digits)整数向量(4x32bits)
-    //
-    // `u64::MAX == 1844_67440737_09551615`(20bytes)
-    // 向量只看高位3个分量
-    let base10e8_32bit = unsafe {
+    let base10e8_32bit = {
         let digit_value_base10_8bit = _mm256_set_epi8(
             1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1,
             10, 1, 10, 1, 10, 1, 10,
@@ -67,28 +194,196 @@ pub fn parse_u64(s: &str) -> Option<u64> {
         // = [0.., 1234]
         let base10e4_16bit = _mm_maddubs_epi16(base10e2_8bit, digit_value_base10e2_8bit);
 
-        let base10e8_32bit = _mm_madd_epi16(base10e4_16bit, digit_value_base10e4_16bit);
-        base10e8_32bit
+        _mm_madd_epi16(base10e4_16bit, digit_value_base10e4_16bit)
     };
 
-    // 使用标量计算剩余数的十进制求和
-    unsafe {
-        let res_1digit = _mm_extract_epi32(base10e8_32bit, 3) as u64;
-        if mask & 0xFFFFFFFF == 0 {
-            return Some(res_1digit);
+    Some(base10e8_32bit)
+}
+
+/// 把最多16字节的数字串折叠成2组8位数字 `(高, 低)`，只用128位向量；有非数字的字节时返回 `None`
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn fold16(bytes: &[u8]) -> Option<(u32, u32)> {
+    let end = bytes.as_ptr_range().end;
+    let mask = (0xFFFF_0000_u32 >> bytes.len()) as u16;
+
+    let base10_8bit = unsafe {
+        let s_bytes_v = _mm_maskz_loadu_epi8(mask, end.wrapping_sub(16).cast());
+        let base10_8bit = _mm_maskz_sub_epi8(mask, s_bytes_v, _mm_set1_epi8('0' as i8));
+        if _mm_mask_cmpgt_epu8_mask(mask, base10_8bit, _mm_set1_epi8(9)) != 0 {
+            return None;
+        }
+        base10_8bit
+    };
+
+    // 每2位、4位一组，4位一组的结果不超过9999，可以饱和地压缩成16位再两两合并
+    let base10e2_16bit = _mm_maddubs_epi16(
+        base10_8bit,
+        _mm_set_epi8(1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10),
+    );
+    let base10e4_32bit = _mm_madd_epi16(
+        base10e2_16bit,
+        _mm_set_epi16(1, 100, 1, 100, 1, 100, 1, 100),
+    );
+    let base10e4_16bit = _mm_packus_epi32(base10e4_32bit, base10e4_32bit);
+    let base10e8_32bit = _mm_madd_epi16(
+        base10e4_16bit,
+        _mm_set_epi16(1, 10000, 1, 10000, 1, 10000, 1, 10000),
+    );
+
+    Some((
+        _mm_cvtsi128_si32(base10e8_32bit) as u32,
+        _mm_extract_epi32(base10e8_32bit, 1) as u32,
+    ))
+}
+
+macro_rules! parse_unsigned {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析`", stringify!($t), "`，规则见 [`parse`](super::parse)")]
+        ///
+        /// # Safety
+        /// - CPU需要支持 `avx512bw` 和 `avx512vl`
+        #[inline]
+        #[target_feature(enable = "avx512bw,avx512vl")]
+        pub fn $name(s: &str) -> Option<$t> {
+            $digits(unsigned_digits(s)?)
         }
+    )*};
+}
+
+parse_unsigned!(
+    parse_u8: u8 => u8_digits,
+    parse_u16: u16 => u16_digits,
+    parse_u32: u32 => u32_digits,
+    parse_u64: u64 => u64_digits,
+    parse_u128: u128 => u128_digits
+);
 
-        let middle_part = _mm_extract_epi32(base10e8_32bit, 2) as u64;
-        let res_2digit = res_1digit + 1_0000_0000 * middle_part;
-        if mask & 0xFFFF == 0 {
-            return Some(res_2digit);
+macro_rules! parse_signed {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析`", stringify!($t), "`，规则见 [`parse`](super::parse)")]
+        ///
+        /// # Safety
+        /// - CPU需要支持 `avx512bw` 和 `avx512vl`
+        #[inline]
+        #[target_feature(enable = "avx512bw,avx512vl")]
+        pub fn $name(s: &str) -> Option<$t> {
+            let (neg, digits) = split_sign(s.as_bytes());
+            if digits.is_empty() {
+                return None;
+            }
+            apply_sign(neg, $digits(digits)? as u64, <$t>::MAX as u64).map(|x| x as $t)
         }
+    )*};
+}
 
-        let high_part = _mm_extract_epi32(base10e8_32bit, 1) as u64;
-        if high_part > 1844 || res_2digit > 67440737_09551615 {
-            return None;
-        } else {
-            return Some(res_2digit + 1_0000_0000_0000_0000 * high_part);
+parse_signed!(
+    parse_i64: i64 => u64_digits,
+    parse_i32: i32 => u32_digits,
+    parse_i16: i16 => u16_digits,
+    parse_i8: i8 => u8_digits
+);
+
+macro_rules! try_parse {
+    ($signed:literal: $($name:ident: $t:ty => $parse:ident),*) => {$(
+        #[doc = concat!("与 [`", stringify!($parse), "`] 相同，失败时返回原因，第一个非数字字节的位置由向量比较的掩码得到")]
+        ///
+        /// # Safety
+        /// - CPU需要支持 `avx512bw` 和 `avx512vl`
+        #[inline]
+        #[target_feature(enable = "avx512bw,avx512vl")]
+        pub fn $name(s: &str) -> Result<$t, ParseIntError> {
+            $parse(s).ok_or_else(|| {
+                parse_error(s, $signed, <$t>::MAX as u128, |bytes| first_nondigit(bytes))
+            })
         }
-    }
+    )*};
 }
+
+try_parse!(false:
+    try_parse_u8: u8 => parse_u8,
+    try_parse_u16: u16 => parse_u16,
+    try_parse_u32: u32 => parse_u32,
+    try_parse_u64: u64 => parse_u64,
+    try_parse_u128: u128 => parse_u128
+);
+
+try_parse!(true:
+    try_parse_i8: i8 => parse_i8,
+    try_parse_i16: i16 => parse_i16,
+    try_parse_i32: i32 => parse_i32,
+    try_parse_i64: i64 => parse_i64
+);
+
+/// 每次比较32字节，第一个非数字字节的下标是掩码的 `trailing_zeros`
+#[inline]
+#[target_feature(enable = "avx512bw,avx512vl")]
+fn first_nondigit(bytes: &[u8]) -> Option<usize> {
+    bytes.chunks(32).enumerate().find_map(|(i, chunk)| {
+        let mask = (u64::MAX >> (64 - chunk.len())) as u32;
+        let nondigits = unsafe {
+            let s_bytes_v = _mm256_maskz_loadu_epi8(mask, chunk.as_ptr().cast());
+            let base10_8bit = _mm256_maskz_sub_epi8(mask, s_bytes_v, _mm256_set1_epi8('0' as i8));
+            _mm256_mask_cmpgt_epu8_mask(mask, base10_8bit, _mm256_set1_epi8(9))
+        };
+        (nondigits != 0).then(|| 32 * i + nondigits.trailing_zeros() as usize)
+    })
+}
+
+macro_rules! parse_unsigned_prefix {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析开头的`", stringify!($t), "`，返回值和用掉的字节数（包括`+`），规则见 [`normal::", stringify!($name), "`](super::normal::", stringify!($name), ")")]
+        ///
+        /// 数字的结尾由非数字字节的掩码得到，而不是整个输入都要是数字
+        ///
+        /// # Safety
+        /// - CPU需要支持 `avx512bw` 和 `avx512vl`
+        #[inline]
+        #[target_feature(enable = "avx512bw,avx512vl")]
+        pub fn $name(bytes: &[u8]) -> Option<($t, usize)> {
+            let digits = strip_plus(bytes);
+            let len = first_nondigit(digits).unwrap_or(digits.len());
+            if len == 0 {
+                return None;
+            }
+            let sign_len = bytes.len() - digits.len();
+            Some(($digits(&digits[..len])?, sign_len + len))
+        }
+    )*};
+}
+
+parse_unsigned_prefix!(
+    parse_u8_prefix: u8 => u8_digits,
+    parse_u16_prefix: u16 => u16_digits,
+    parse_u32_prefix: u32 => u32_digits,
+    parse_u64_prefix: u64 => u64_digits,
+    parse_u128_prefix: u128 => u128_digits
+);
+
+macro_rules! parse_signed_prefix {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析开头的`", stringify!($t), "`，返回值和用掉的字节数（包括符号），规则见 [`normal::", stringify!($name), "`](super::normal::", stringify!($name), ")")]
+        ///
+        /// # Safety
+        /// - CPU需要支持 `avx512bw` 和 `avx512vl`
+        #[inline]
+        #[target_feature(enable = "avx512bw,avx512vl")]
+        pub fn $name(bytes: &[u8]) -> Option<($t, usize)> {
+            let (neg, digits) = split_sign(bytes);
+            let len = first_nondigit(digits).unwrap_or(digits.len());
+            if len == 0 {
+                return None;
+            }
+            let sign_len = bytes.len() - digits.len();
+            let value = apply_sign(neg, $digits(&digits[..len])? as u64, <$t>::MAX as u64)?;
+            Some((value as $t, sign_len + len))
+        }
+    )*};
+}
+
+parse_signed_prefix!(
+    parse_i64_prefix: i64 => u64_digits,
+    parse_i32_prefix: i32 => u32_digits,
+    parse_i16_prefix: i16 => u16_digits,
+    parse_i8_prefix: i8 => u8_digits
+);
@@ -1,7 +1,249 @@
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx512f",
-))]
+use std::{error::Error, fmt, sync::OnceLock};
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
 /// [Parsing integers quickly with AVX-512](https://lemire.me/blog/2023/09/22/parsing-integers-quickly-with-AVX-512/)
 pub mod avx;
 pub mod normal;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// 可以解析的整数类型：`u8`、`u16`、`u32`、`u64`、`u128`、`i8`、`i16`、`i32`、`i64`
+pub trait ParseInt: Sized + sealed::Sealed {
+    #[doc(hidden)]
+    fn dispatch_parse(s: &str) -> Option<Self>;
+
+    #[doc(hidden)]
+    fn dispatch_try_parse(s: &str) -> Result<Self, ParseIntError>;
+
+    #[doc(hidden)]
+    fn dispatch_parse_prefix(bytes: &[u8]) -> Option<(Self, usize)>;
+}
+
+macro_rules! impl_parse_int {
+    ($($t:ty => $name:ident, $try_name:ident, $prefix_name:ident),*) => {$(
+        impl sealed::Sealed for $t {}
+
+        impl ParseInt for $t {
+            fn dispatch_parse(s: &str) -> Option<Self> {
+                static PARSE: OnceLock<fn(&str) -> Option<$t>> = OnceLock::new();
+
+                let parse = PARSE.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+                        return |s| unsafe { avx::$name(s) };
+                    }
+
+                    normal::$name
+                });
+
+                parse(s)
+            }
+
+            fn dispatch_try_parse(s: &str) -> Result<Self, ParseIntError> {
+                static TRY_PARSE: OnceLock<fn(&str) -> Result<$t, ParseIntError>> = OnceLock::new();
+
+                let try_parse = TRY_PARSE.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+                        return |s| unsafe { avx::$try_name(s) };
+                    }
+
+                    normal::$try_name
+                });
+
+                try_parse(s)
+            }
+
+            fn dispatch_parse_prefix(bytes: &[u8]) -> Option<(Self, usize)> {
+                type ParsePrefix = fn(&[u8]) -> Option<($t, usize)>;
+                static PARSE_PREFIX: OnceLock<ParsePrefix> = OnceLock::new();
+
+                let parse_prefix = PARSE_PREFIX.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+                        return |bytes| unsafe { avx::$prefix_name(bytes) };
+                    }
+
+                    normal::$prefix_name
+                });
+
+                parse_prefix(bytes)
+            }
+        }
+
+        #[doc = concat!("解析`", stringify!($t), "`，即 `parse::<", stringify!($t), ">(s)`")]
+        pub fn $name(s: &str) -> Option<$t> {
+            parse(s)
+        }
+
+        #[doc = concat!("解析`", stringify!($t), "`，失败时返回原因，即 `try_parse::<", stringify!($t), ">(s)`")]
+        pub fn $try_name(s: &str) -> Result<$t, ParseIntError> {
+            try_parse(s)
+        }
+
+        #[doc = concat!("解析开头的`", stringify!($t), "`，即 `parse_prefix::<", stringify!($t), ">(bytes)`")]
+        pub fn $prefix_name(bytes: &[u8]) -> Option<($t, usize)> {
+            parse_prefix(bytes)
+        }
+    )*};
+}
+
+impl_parse_int!(
+    u8 => parse_u8, try_parse_u8, parse_u8_prefix,
+    u16 => parse_u16, try_parse_u16, parse_u16_prefix,
+    u32 => parse_u32, try_parse_u32, parse_u32_prefix,
+    u64 => parse_u64, try_parse_u64, parse_u64_prefix,
+    u128 => parse_u128, try_parse_u128, parse_u128_prefix,
+    i8 => parse_i8, try_parse_i8, parse_i8_prefix,
+    i16 => parse_i16, try_parse_i16, parse_i16_prefix,
+    i32 => parse_i32, try_parse_i32, parse_i32_prefix,
+    i64 => parse_i64, try_parse_i64, parse_i64_prefix
+);
+
+/// 解析整数，如 `parse::<u32>(s)`，运行时检测CPU特性，选择最快的实现（检测结果只做一次）
+///
+/// 所有实现的规则都与 `str::parse` 相同：
+/// - 开头可以有一个`+`，有符号数也可以是`-`
+/// - 之后至少有一位数字，只能是ASCII数字，不能有空白
+/// - 前导零的个数不限
+/// - 空字符串、只有符号、超出类型的范围时返回 `None`
+pub fn parse<T: ParseInt>(s: &str) -> Option<T> {
+    T::dispatch_parse(s)
+}
+
+/// 与 [`parse`] 相同，失败时返回原因，运行时选择最快的实现
+pub fn try_parse<T: ParseInt>(s: &str) -> Result<T, ParseIntError> {
+    T::dispatch_try_parse(s)
+}
+
+/// 解析字节串开头的整数，返回值和用掉的字节数，运行时选择最快的实现
+///
+/// 开头可以有一个符号（规则同 [`parse`]），之后的数字遇到第一个非数字字节就停止，后面的内容不影响结果，
+/// 如 `parse_prefix::<u32>(b"123,456") == Some((123, 3))`；没有数字或超出范围时返回 `None`
+pub fn parse_prefix<T: ParseInt>(bytes: &[u8]) -> Option<(T, usize)> {
+    T::dispatch_parse_prefix(bytes)
+}
+
+/// 解析失败的原因，与 `std::num::IntErrorKind` 对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntErrorKind {
+    /// 空字符串
+    Empty,
+    /// 有不是数字的字节，包括只有符号、无符号数带`-`
+    InvalidDigit,
+    /// 超过类型的最大值
+    PosOverflow,
+    /// 小于类型的最小值
+    NegOverflow,
+}
+
+/// 解析整数失败，与 `std::num::ParseIntError` 的原因相同，另外记录了第一个非数字字节的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseIntError {
+    kind: IntErrorKind,
+    offset: Option<usize>,
+}
+
+impl ParseIntError {
+    pub fn kind(&self) -> IntErrorKind {
+        self.kind
+    }
+
+    /// [`IntErrorKind::InvalidDigit`] 时第一个非数字字节的下标；只有符号时是符号之后的位置
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+}
+
+impl fmt::Display for ParseIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, self.offset) {
+            (IntErrorKind::Empty, _) => f.write_str("cannot parse integer from empty string"),
+            (IntErrorKind::InvalidDigit, Some(offset)) => {
+                write!(f, "invalid digit found in string at byte {offset}")
+            }
+            (IntErrorKind::InvalidDigit, None) => f.write_str("invalid digit found in string"),
+            (IntErrorKind::PosOverflow, _) => f.write_str("number too large to fit in target type"),
+            (IntErrorKind::NegOverflow, _) => f.write_str("number too small to fit in target type"),
+        }
+    }
+}
+
+impl Error for ParseIntError {}
+
+/// 去掉开头的一个`+`或`-`，返回是否为负数和剩下的部分
+fn split_sign(bytes: &[u8]) -> (bool, &[u8]) {
+    match bytes.first() {
+        Some(b'-') => (true, &bytes[1..]),
+        Some(b'+') => (false, &bytes[1..]),
+        _ => (false, bytes),
+    }
+}
+
+/// 去掉无符号数开头可以有的一个`+`
+fn strip_plus(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(b"+").unwrap_or(bytes)
+}
+
+/// 无符号数开头可以有一个`+`，返回去掉后的数字部分；为空时不是合法的数字
+fn unsigned_digits(s: &str) -> Option<&[u8]> {
+    let digits = strip_plus(s.as_bytes());
+    (!digits.is_empty()).then_some(digits)
+}
+
+/// 由符号和绝对值得到有符号数，`max`是正数的最大值，负数可以多一个（如 `i64::MIN`）
+fn apply_sign(neg: bool, abs: u64, max: u64) -> Option<i64> {
+    if neg {
+        (abs <= max + 1).then(|| (abs as i64).wrapping_neg())
+    } else {
+        (abs <= max).then_some(abs as i64)
+    }
+}
+
+/// 按 `str::parse` 的规则找出`s`解析失败的原因，`max`是类型的最大值，`first_nondigit`找第一个非数字字节。
+///
+/// `str::parse` 从左到右逐位累加，所以第一个非数字字节之前的数字已经超出范围时报告溢出，否则报告非法数字。
+fn parse_error(
+    s: &str,
+    signed: bool,
+    max: u128,
+    first_nondigit: impl FnOnce(&[u8]) -> Option<usize>,
+) -> ParseIntError {
+    let error = |kind, offset| ParseIntError { kind, offset };
+    if s.is_empty() {
+        return error(IntErrorKind::Empty, None);
+    }
+
+    let (neg, digits) = if signed {
+        split_sign(s.as_bytes())
+    } else {
+        (false, strip_plus(s.as_bytes()))
+    };
+    let sign_len = s.len() - digits.len();
+    let end = if digits.is_empty() {
+        Some(0)
+    } else {
+        first_nondigit(digits)
+    };
+
+    let value = digits[..end.unwrap_or(digits.len())]
+        .iter()
+        .try_fold(0_u128, |acc, &d| {
+            acc.checked_mul(10)?.checked_add((d - b'0') as u128)
+        });
+    let bound = if neg { max + 1 } else { max };
+    // 只在解析失败后调用；万一`s`是合法的整数（各实现不一致），保守地报告非法数字，不panic
+    debug_assert!(
+        end.is_some() || value.is_none_or(|value| value > bound),
+        "`{s}` is a valid integer"
+    );
+    match value {
+        Some(value) if value <= bound => {
+            error(IntErrorKind::InvalidDigit, end.map(|end| sign_len + end))
+        }
+        _ if neg => error(IntErrorKind::NegOverflow, None),
+        _ => error(IntErrorKind::PosOverflow, None),
+    }
+}
@@ -1,10 +1,138 @@
-pub fn parse_u64(s: &str) -> Option<u64> {
-    let mut res = 0_u64;
-    let iter = s.chars().map(|d| (d as u64).checked_sub('0' as u64));
-    for digit in iter {
-        let digit = digit.filter(|d| *d <= 9)?;
-        res = res.checked_mul(10_u64)?.checked_add(digit)?;
-    }
+use super::{apply_sign, parse_error, split_sign, strip_plus, unsigned_digits, ParseIntError};
 
-    Some(res)
+macro_rules! parse_unsigned {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        /// 只有数字的字符串，不能为空
+        fn $digits(bytes: &[u8]) -> Option<$t> {
+            let mut res: $t = 0;
+            for &b in bytes {
+                let digit = b.wrapping_sub(b'0');
+                if digit > 9 {
+                    return None;
+                }
+                res = res.checked_mul(10)?.checked_add(digit as $t)?;
+            }
+
+            Some(res)
+        }
+
+        #[doc = concat!("解析`", stringify!($t), "`，规则见 [`parse`](super::parse)")]
+        pub fn $name(s: &str) -> Option<$t> {
+            $digits(unsigned_digits(s)?)
+        }
+    )*};
+}
+
+parse_unsigned!(
+    parse_u8: u8 => u8_digits,
+    parse_u16: u16 => u16_digits,
+    parse_u32: u32 => u32_digits,
+    parse_u64: u64 => u64_digits,
+    parse_u128: u128 => u128_digits
+);
+
+macro_rules! parse_signed {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析`", stringify!($t), "`，规则见 [`parse`](super::parse)")]
+        pub fn $name(s: &str) -> Option<$t> {
+            let (neg, digits) = split_sign(s.as_bytes());
+            if digits.is_empty() {
+                return None;
+            }
+            apply_sign(neg, $digits(digits)? as u64, <$t>::MAX as u64).map(|x| x as $t)
+        }
+    )*};
+}
+
+parse_signed!(
+    parse_i64: i64 => u64_digits,
+    parse_i32: i32 => u32_digits,
+    parse_i16: i16 => u16_digits,
+    parse_i8: i8 => u8_digits
+);
+
+macro_rules! try_parse {
+    ($signed:literal: $($name:ident: $t:ty => $parse:ident),*) => {$(
+        #[doc = concat!("与 [`", stringify!($parse), "`] 相同，失败时返回原因")]
+        pub fn $name(s: &str) -> Result<$t, ParseIntError> {
+            $parse(s).ok_or_else(|| {
+                parse_error(s, $signed, <$t>::MAX as u128, |bytes| {
+                    bytes.iter().position(|b| !b.is_ascii_digit())
+                })
+            })
+        }
+    )*};
+}
+
+try_parse!(false:
+    try_parse_u8: u8 => parse_u8,
+    try_parse_u16: u16 => parse_u16,
+    try_parse_u32: u32 => parse_u32,
+    try_parse_u64: u64 => parse_u64,
+    try_parse_u128: u128 => parse_u128
+);
+
+try_parse!(true:
+    try_parse_i8: i8 => parse_i8,
+    try_parse_i16: i16 => parse_i16,
+    try_parse_i32: i32 => parse_i32,
+    try_parse_i64: i64 => parse_i64
+);
+
+/// 开头连续的ASCII数字的个数
+fn digits_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(bytes.len())
+}
+
+macro_rules! parse_unsigned_prefix {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析开头的`", stringify!($t), "`，遇到第一个非数字字节停止，返回值和用掉的字节数（包括`+`）")]
+        ///
+        /// 没有数字或超出范围时返回 `None`
+        pub fn $name(bytes: &[u8]) -> Option<($t, usize)> {
+            let digits = strip_plus(bytes);
+            let len = digits_len(digits);
+            if len == 0 {
+                return None;
+            }
+            let sign_len = bytes.len() - digits.len();
+            Some(($digits(&digits[..len])?, sign_len + len))
+        }
+    )*};
 }
+
+parse_unsigned_prefix!(
+    parse_u8_prefix: u8 => u8_digits,
+    parse_u16_prefix: u16 => u16_digits,
+    parse_u32_prefix: u32 => u32_digits,
+    parse_u64_prefix: u64 => u64_digits,
+    parse_u128_prefix: u128 => u128_digits
+);
+
+macro_rules! parse_signed_prefix {
+    ($($name:ident: $t:ty => $digits:ident),*) => {$(
+        #[doc = concat!("解析开头的`", stringify!($t), "`，遇到第一个非数字字节停止，返回值和用掉的字节数（包括符号）")]
+        ///
+        /// 没有数字或超出范围时返回 `None`
+        pub fn $name(bytes: &[u8]) -> Option<($t, usize)> {
+            let (neg, digits) = split_sign(bytes);
+            let len = digits_len(digits);
+            if len == 0 {
+                return None;
+            }
+            let sign_len = bytes.len() - digits.len();
+            let value = apply_sign(neg, $digits(&digits[..len])? as u64, <$t>::MAX as u64)?;
+            Some((value as $t, sign_len + len))
+        }
+    )*};
+}
+
+parse_signed_prefix!(
+    parse_i64_prefix: i64 => u64_digits,
+    parse_i32_prefix: i32 => u32_digits,
+    parse_i16_prefix: i16 => u16_digits,
+    parse_i8_prefix: i8 => u8_digits
+);
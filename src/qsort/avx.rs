@@ -20,106 +20,417 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod element;
+mod pairs;
+
 #[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::{
-    __m512i, _mm512_cmp_epi32_mask, _mm512_loadu_epi32, _mm512_mask_compressstoreu_epi32,
-    _mm512_mask_mov_epi32, _mm512_maskz_loadu_epi32, _mm512_maskz_set1_epi32, _mm512_max_epi32,
-    _mm512_min_epi32, _mm512_or_epi32, _mm512_or_si512, _mm512_permutexvar_epi32,
-    _mm512_set1_epi32, _mm512_set_epi32, _mm512_storeu_epi32, _MM_CMPINT_LE,
-};
+use std::arch::x86_64::{__m512i, _mm512_set_epi32, _mm512_set_epi64};
 
 #[cfg(target_arch = "x86")]
-use std::arch::x86_64::{
-    __m512i, _mm512_cmp_epi32_mask, _mm512_loadu_epi32, _mm512_mask_compressstoreu_epi32,
-    _mm512_mask_mov_epi32, _mm512_maskz_loadu_epi32, _mm512_maskz_set1_epi32, _mm512_max_epi32,
-    _mm512_min_epi32, _mm512_or_epi32, _mm512_or_si512, _mm512_permutexvar_epi32,
-    _mm512_set1_epi32, _mm512_set_epi32, _mm512_storeu_epi32, _MM_CMPINT_LE,
+use std::arch::x86::{__m512i, _mm512_set_epi32, _mm512_set_epi64};
+use std::{cmp::Reverse, mem};
+
+pub use element::SimdElement;
+pub use pairs::{argsort, sort_by_key, sort_pairs};
+
+use super::{
+    as_reverse, depth_limit, heapsort, normal,
+    parallel::{par_sort, ParallelConfig},
+    split_nth, swap_unchecked, TotalOrder,
 };
-use std::mem::size_of;
 
-const S: usize = size_of::<__m512i>() / size_of::<i32>();
-pub fn qsort(arr: &mut [i32]) {
+/// [`qsort`] 默认用双调排序网络直接排序的向量个数，见 [`qsort_with_cutoff`]
+pub const DEFAULT_BITONIC_VECTORS: usize = 8;
+
+/// 双调排序网络最多用的向量个数
+pub const MAX_BITONIC_VECTORS: usize = 16;
+
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn qsort<T: SimdElement>(arr: &mut [T]) {
+    qsort_with_cutoff(arr, DEFAULT_BITONIC_VECTORS * T::LANES);
+}
+
+/// 不超过`cutoff`个元素的子数组不再划分，直接用双调排序网络排序。
+///
+/// 开头的有序段超过一半时只排序剩下的部分再合并，这时会分配剩下部分大小的缓冲区（最多 `arr.len() / 2` 个元素），
+/// 其它情况不分配内存。
+///
+/// `cutoff`会被限制在 `2 * T::LANES ..= MAX_BITONIC_VECTORS * T::LANES` 之间
+/// （32位元素为 32..=256，64位元素为 16..=128）。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn qsort_with_cutoff<T: SimdElement>(arr: &mut [T], cutoff: usize) {
+    let cutoff = cutoff.clamp(2 * T::LANES, MAX_BITONIC_VECTORS * T::LANES);
+    if arr.len() > cutoff && sort_presorted(arr, cutoff) {
+        return;
+    }
+    qsort_impl(arr, depth_limit(arr.len()), None, cutoff);
+}
+
+/// 先检查开头的有序段：整个有序直接返回，整个逆序就原地翻转；
+/// 有序段（逆序的先翻转）超过一半时只排序剩下的部分，再与有序段合并，
+/// 合并需要与剩下的部分等长（不超过一半）的缓冲区。
+/// 返回是否已经排好序，否则需要完整地排序。
+#[target_feature(enable = "avx512f")]
+fn sort_presorted<T: SimdElement>(arr: &mut [T], cutoff: usize) -> bool {
     let len = arr.len();
-    match len {
-        ..=1 => return,
-        2..16 => unsafe {
-            let ptr = arr.as_mut_ptr();
-            let rest = S - len;
-            let mut v1 = _mm512_or_si512(
-                _mm512_maskz_loadu_epi32(0xFFFF >> rest, ptr),
-                _mm512_maskz_set1_epi32(0xFFFF << len, i32::MAX),
-            );
-            bitonic_sort1_impl(&mut v1);
-            _mm512_mask_compressstoreu_epi32(ptr.cast(), 0xFFFF >> rest, v1);
-        },
-        16 => unsafe {
-            let ptr = arr.as_mut_ptr();
-            let mut v1 = _mm512_loadu_epi32(ptr);
-            bitonic_sort1_impl(&mut v1);
-            _mm512_storeu_epi32(ptr, v1);
-        },
-
-        17..32 => unsafe {
-            let ptr = arr.as_mut_ptr();
-            let rest = 2 * S - len;
-            let last_size = len - S;
-            let mut v1 = _mm512_loadu_epi32(ptr);
-            let mut v2 = _mm512_or_epi32(
-                _mm512_maskz_loadu_epi32(0xFFFF >> rest, ptr.offset(S as isize)),
-                _mm512_maskz_set1_epi32(0xFFFF << last_size, i32::MAX),
-            );
-            bitonic_sort2_impl(&mut v1, &mut v2);
-            _mm512_storeu_epi32(ptr, v1);
-            _mm512_mask_compressstoreu_epi32(ptr.offset(S as isize).cast(), 0xFFFF >> rest, v2);
-        },
-
-        32 => unsafe {
-            let ptr = arr.as_mut_ptr();
-            let mut v1 = _mm512_loadu_epi32(ptr);
-            let mut v2 = _mm512_loadu_epi32(ptr.offset(S as isize));
-            bitonic_sort2_impl(&mut v1, &mut v2);
-
-            _mm512_storeu_epi32(ptr, v1);
-            _mm512_storeu_epi32(ptr.offset(S as isize), v2);
-        },
-        _ => unsafe {
-            let part = partition(arr);
-            qsort(arr.get_unchecked_mut(..part));
-            qsort(arr.get_unchecked_mut(part + 1..));
-        },
+    let mut run = sorted_prefix(arr);
+    if run == len {
+        return true;
+    }
+    if run == 1 {
+        run = run_len(arr, |a, b| unsafe { T::cmp_le_mask(b, a) }, |a, b| b <= a);
+        if run == len {
+            reverse_in_place(arr);
+            return true;
+        }
+        if run >= len / 2 {
+            reverse_in_place(&mut arr[..run]);
+        }
+    }
+    if run < len / 2 {
+        return false;
+    }
+
+    let rest = &mut arr[run..];
+    // 有NaN时填充的值不一定合并到最后
+    if rest.iter().any(|x| x.partial_cmp(x).is_none()) {
+        return false;
     }
+    qsort_impl(rest, depth_limit(rest.len()), None, cutoff);
+    merge_back(arr, run);
+    true
 }
 
-#[inline(always)]
-unsafe fn get_pivot(arr: &[i32]) -> (usize, i32) {
-    let mid = arr.len() / 2;
-    let right = arr.len() - 1;
-    let a = *arr.get_unchecked(0);
-    let b = *arr.get_unchecked(mid);
-    let c = *arr.get_unchecked(right);
-    if (a..c).contains(&b) {
-        (mid, b)
-    } else if (b..c).contains(&a) {
-        (0, a)
+/// 合并有序的`arr[..mid]`和`arr[mid..]`：只把较短的`arr[mid..]`复制到缓冲区，再从后往前合并，
+/// 写入的位置总在还没读的`arr[..mid]`之后
+fn merge_back<T: Copy + PartialOrd>(arr: &mut [T], mid: usize) {
+    let buf = arr[mid..].to_vec();
+    let (mut i, mut j) = (mid, buf.len());
+    while i > 0 && j > 0 {
+        let take_left = arr[i - 1] > buf[j - 1];
+        arr[i + j - 1] = if take_left { arr[i - 1] } else { buf[j - 1] };
+        i -= take_left as usize;
+        j -= !take_left as usize;
+    }
+    arr[..j].copy_from_slice(&buf[..j]);
+}
+
+/// 与 `slice::is_sorted` 相同：相邻的元素都满足 `a <= b`，有NaN时返回false
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn is_sorted<T: SimdElement>(arr: &[T]) -> bool {
+    sorted_prefix(arr) == arr.len()
+}
+
+/// 开头非递减的部分的长度
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn sorted_prefix<T: SimdElement>(arr: &[T]) -> usize {
+    run_len(arr, |a, b| unsafe { T::cmp_le_mask(a, b) }, |a, b| a <= b)
+}
+
+/// 开头相邻元素都满足`ordered`的部分的长度，`to_mask`是`ordered`的向量版本
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn run_len<T: SimdElement>(
+    arr: &[T],
+    to_mask: impl Fn(T::Vector, T::Vector) -> u16,
+    ordered: impl Fn(T, T) -> bool,
+) -> usize {
+    let s = T::LANES;
+    let len = arr.len();
+    let ptr = arr.as_ptr();
+    let mut i = 0;
+    while i + s < len {
+        let mask = unsafe { to_mask(T::loadu(ptr.add(i)), T::loadu(ptr.add(i + 1))) };
+        if mask != T::FULL_MASK {
+            return i + (!mask).trailing_zeros() as usize + 1;
+        }
+        i += s;
+    }
+    while i + 1 < len && ordered(arr[i], arr[i + 1]) {
+        i += 1;
+    }
+    (i + 1).min(len)
+}
+
+/// 原地翻转：两端各取一个向量，分量逆序后交换位置
+#[target_feature(enable = "avx512f")]
+fn reverse_in_place<T: SimdElement>(arr: &mut [T]) {
+    let s = T::LANES;
+    let (mut left, mut right) = (0, arr.len());
+    let ptr = arr.as_mut_ptr();
+    while right - left >= 2 * s {
+        unsafe {
+            let l = T::loadu(ptr.add(left));
+            let r = T::loadu(ptr.add(right - s));
+            T::storeu(ptr.add(left), reverse::<T>(r));
+            T::storeu(ptr.add(right - s), reverse::<T>(l));
+        }
+        left += s;
+        right -= s;
+    }
+    arr[left..right].reverse();
+}
+
+/// 递归深度超过`limit`后改用堆排序；只递归较短的一边，较长的一边继续循环。
+/// `pred`见 [`partition`]。
+#[target_feature(enable = "avx512f")]
+fn qsort_impl<T: SimdElement>(
+    mut arr: &mut [T],
+    mut limit: u32,
+    mut pred: Option<T>,
+    cutoff: usize,
+) {
+    loop {
+        let len = arr.len();
+        match len {
+            ..=1 => (),
+            _ if len <= cutoff => unsafe { bitonic_sort_small(arr) },
+            _ if limit == 0 => heapsort(arr, |v, a, b| unsafe { swap_unchecked(v, a, b) }),
+            _ => unsafe {
+                limit -= 1;
+                let (lt, gt) = partition(arr, pred);
+                let pivot = *arr.get_unchecked(lt);
+                let (left, right) = mem::take(&mut arr).split_at_mut_unchecked(lt);
+                let right = right.get_unchecked_mut(gt - lt..);
+                if left.len() < right.len() {
+                    qsort_impl(left, limit, Some(pivot), cutoff);
+                    arr = right;
+                } else {
+                    qsort_impl(right, limit, pred, cutoff);
+                    arr = left;
+                    pred = Some(pivot);
+                }
+                continue;
+            },
+        }
+        return;
+    }
+}
+
+/// 按长度选用1、2、4、8或16个向量的双调排序网络
+///
+/// # Safety
+/// - 2 <= arr.len() <= MAX_BITONIC_VECTORS * T::LANES
+#[inline]
+#[target_feature(enable = "avx512f")]
+pub(crate) unsafe fn bitonic_sort_small<T: SimdElement>(arr: &mut [T]) {
+    unsafe {
+        match arr.len().div_ceil(T::LANES) {
+            1 => bitonic_sort_n::<T, 1>(arr),
+            2 => bitonic_sort_n::<T, 2>(arr),
+            3..=4 => bitonic_sort_n::<T, 4>(arr),
+            5..=8 => bitonic_sort_n::<T, 8>(arr),
+            _ => bitonic_sort_n::<T, 16>(arr),
+        }
+    }
+}
+
+/// 读入`N`个向量排序后写回，不满的部分用`T::PADDING`填充
+///
+/// # Safety
+/// - arr.len() <= N * T::LANES
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn bitonic_sort_n<T: SimdElement, const N: usize>(arr: &mut [T]) {
+    let s = T::LANES;
+    let len = arr.len();
+    let ptr = arr.as_mut_ptr();
+    let full = len / s;
+    let rest = len % s;
+    // `rest == 0` 时不用
+    let mask = T::FULL_MASK >> ((s - rest) % s);
+
+    unsafe {
+        let mut inputs = [T::set1(T::PADDING); N];
+        for (i, input) in inputs.iter_mut().enumerate().take(full) {
+            *input = T::loadu(ptr.add(i * s));
+        }
+        if rest > 0 {
+            inputs[full] = T::mask_loadu(inputs[full], mask, ptr.add(full * s));
+        }
+
+        // 含NaN时填充的值不一定排到最后，不能只取前`len`个分量
+        if len < N * s && inputs.iter().any(|&v| T::cmp_le_mask(v, v) != T::FULL_MASK) {
+            heapsort(arr, |v, a, b| swap_unchecked(v, a, b));
+            return;
+        }
+
+        bitonic_sort_impl::<T, [T::Vector]>(&mut inputs);
+        bitonic_merge_impl::<T, [T::Vector]>(&mut inputs, N);
+
+        for (i, input) in inputs.iter().enumerate().take(full) {
+            T::storeu(ptr.add(i * s), *input);
+        }
+        if rest > 0 {
+            T::mask_compressstoreu(ptr.add(full * s), mask, inputs[full]);
+        }
+    }
+}
+
+/// 降序排序：对 `Reverse<T>` 排序，划分时比较的掩码反过来，双调排序网络中`min`与`max`互换
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn qsort_desc<T: SimdElement>(arr: &mut [T])
+where
+    Reverse<T>: SimdElement,
+{
+    qsort(as_reverse(arr));
+}
+
+/// 按 IEEE 754 `totalOrder` 排序浮点数，结果与 `sort_unstable_by(f64::total_cmp)` 相同
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn qsort_total<T: TotalOrder>(arr: &mut [T])
+where
+    T::Key: SimdElement,
+{
+    let keys = T::as_keys(arr);
+    T::flip(keys);
+    qsort(keys);
+    T::flip(keys);
+}
+
+/// 多线程排序：前几层在调用者线程里用 [`vector_partition`] 划分，每个线程里用 [`qsort`]
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn par_qsort<T: SimdElement + Send>(arr: &mut [T], config: &ParallelConfig) {
+    par_sort(
+        arr,
+        config,
+        |arr| qsort(arr),
+        |arr, pred| partition(arr, pred),
+    );
+}
+
+/// 重排`arr`使第`k`小的元素在下标`k`处，左边的都不大于它，右边的都不小于它。
+/// 返回值与 `slice::select_nth_unstable` 相同。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `k >= arr.len()`
+#[target_feature(enable = "avx512f")]
+pub fn select_nth_unstable<T: SimdElement>(
+    arr: &mut [T],
+    k: usize,
+) -> (&mut [T], &mut T, &mut [T]) {
+    select_impl(arr, k);
+    split_nth(arr, k)
+}
+
+/// 把最小的`k`个元素按顺序排在`arr[..k]`，其余元素的顺序不确定；`k > arr.len()` 时排序整个数组，不会panic
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn partial_sort<T: SimdElement>(arr: &mut [T], k: usize) {
+    let k = k.min(arr.len());
+    if k == 0 {
+        return;
+    }
+    if k < arr.len() {
+        select_impl(arr, k - 1);
+        qsort(&mut arr[..k - 1]);
     } else {
-        (right, c)
+        qsort(arr);
     }
 }
 
+/// 只在含有第`k`个元素的一边继续划分，较小的数组直接排序
+///
+/// # Panics
+/// - `k >= arr.len()`
+#[target_feature(enable = "avx512f")]
+fn select_impl<T: SimdElement>(mut arr: &mut [T], mut k: usize) {
+    assert!(
+        k < arr.len(),
+        "select index {k} out of range for slice of length {}",
+        arr.len()
+    );
+
+    let mut limit = depth_limit(arr.len());
+    let mut pred = None;
+    while arr.len() > 2 * T::LANES {
+        if limit == 0 {
+            heapsort(arr, |v, a, b| unsafe { swap_unchecked(v, a, b) });
+            return;
+        }
+        limit -= 1;
+
+        let (lt, gt) = partition(arr, pred);
+        let pivot = unsafe { *arr.get_unchecked(lt) };
+        if k < lt {
+            arr = unsafe { mem::take(&mut arr).get_unchecked_mut(..lt) };
+            pred = Some(pivot);
+        } else if k >= gt {
+            arr = unsafe { mem::take(&mut arr).get_unchecked_mut(gt..) };
+            k -= gt;
+        } else {
+            return;
+        }
+    }
+    qsort_impl(arr, limit, pred, DEFAULT_BITONIC_VECTORS * T::LANES);
+}
+
 #[inline(always)]
-fn partition(arr: &mut [i32]) -> usize {
+unsafe fn get_pivot<T: SimdElement>(arr: &[T]) -> (usize, T) {
+    // 不取两端：分区时最前和最后的两个向量会被写到中间，两端的元素不能代表整体
+    let len = arr.len();
+    let (i, j, k) = (len / 4, len / 2, len / 4 * 3);
+    let a = *arr.get_unchecked(i);
+    let b = *arr.get_unchecked(j);
+    let c = *arr.get_unchecked(k);
+    // 三者取中
+    if (a <= b) == (b <= c) {
+        (j, b)
+    } else if (b <= a) == (a <= c) {
+        (i, a)
+    } else {
+        (k, c)
+    }
+}
+
+/// 划分后 `arr[lt..gt]` 都等于枢轴，已在最终位置，返回 `(lt, gt)`。
+///
+/// `pred`是上层的枢轴，这一段的元素都不大于它；选出的枢轴等于`pred`说明有重复的值，
+/// 改用三路划分把相等的元素一次分出来。
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn partition<T: SimdElement>(arr: &mut [T], pred: Option<T>) -> (usize, usize) {
     unsafe {
         let len = arr.len();
         let (idx, pivot) = get_pivot(arr);
-        arr.swap_unchecked(idx, len - 1);
+        swap_unchecked(arr, idx, len - 1);
         // let part = if len <= 2 * S {
         //     normal_partition(arr, pivot)
         // } else {
         //     vector_partition(arr, pivot)
         // };
-        let part = vector_partition(arr, pivot);
-        arr.swap_unchecked(part, len - 1);
-        part
+        if pred.is_some_and(|pred| pred <= pivot) {
+            let (lt, gt) = vector_partition3(arr, pivot);
+            swap_unchecked(arr, gt, len - 1);
+            (lt, gt + 1)
+        } else {
+            let part = vector_partition(arr, pivot);
+            swap_unchecked(arr, part, len - 1);
+            (part, part + 1)
+        }
     }
 }
 
@@ -129,7 +440,7 @@ fn partition(arr: &mut [i32]) -> usize {
 //     for j in 0..arr.len() - 1 {
 //         unsafe {
 //             if *arr.get_unchecked(j) <= pivot {
-//                 arr.swap_unchecked(i, j);
+//                 swap_unchecked(arr, i, j);
 //                 i += 1;
 //             }
 //         }
@@ -138,59 +449,139 @@ fn partition(arr: &mut [i32]) -> usize {
 //     i
 // }
 
-/// Safety
-/// - arr.len() > 2*S
-#[inline(always)]
-pub unsafe fn vector_partition(arr: &mut [i32], pivot: i32) -> usize {
-    let mut left = 0;
-    let mut right = arr.len() - 1;
+/// 划分 `arr[..len - 1]`（最后一个元素是枢轴），小于等于`pivot`的放左边，返回左边的个数
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+/// - arr.len() > 2 * T::LANES
+#[inline]
+#[target_feature(enable = "avx512f")]
+pub unsafe fn vector_partition<T: SimdElement>(arr: &mut [T], pivot: T) -> usize {
+    let pivotvec = T::set1(pivot);
     unsafe {
-        let pivotvec = _mm512_set1_epi32(pivot);
+        let len = arr.len();
+        vector_partition_by_mask(arr.get_unchecked_mut(..len - 1), |val| {
+            T::cmp_le_mask(val, pivotvec)
+        })
+    }
+}
+
+/// 把小于等于`pivot`的元素移到前面，返回它们的个数；元素原来的相对顺序不保留。
+///
+/// 任意长度都可以，不到两个向量的数组用标量划分。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn partition_le<T: SimdElement>(arr: &mut [T], pivot: T) -> usize {
+    let pivotvec = unsafe { T::set1(pivot) };
+    partition_by_mask(
+        arr,
+        |val| unsafe { T::cmp_le_mask(val, pivotvec) },
+        |x| x <= pivot,
+    )
+}
 
-        let left_val = _mm512_loadu_epi32(arr.as_ptr().offset(left as isize));
+/// 把小于`pivot`的元素移到前面，返回它们的个数；元素原来的相对顺序不保留
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn partition_lt<T: SimdElement>(arr: &mut [T], pivot: T) -> usize {
+    let pivotvec = unsafe { T::set1(pivot) };
+    partition_by_mask(
+        arr,
+        |val| unsafe { T::cmp_lt_mask(val, pivotvec) },
+        |x| x < pivot,
+    )
+}
+
+/// 把在 `low..=high` 之间的元素移到前面，返回它们的个数；元素原来的相对顺序不保留
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn partition_between<T: SimdElement>(arr: &mut [T], low: T, high: T) -> usize {
+    let (lowvec, highvec) = unsafe { (T::set1(low), T::set1(high)) };
+    partition_by_mask(
+        arr,
+        |val| unsafe { T::cmp_le_mask(lowvec, val) & T::cmp_le_mask(val, highvec) },
+        |x| (low..=high).contains(&x),
+    )
+}
+
+/// 把`to_mask`选中的元素移到前面，返回它们的个数；元素原来的相对顺序不保留。
+///
+/// `to_mask`对一个向量（[`SimdElement::LANES`]个元素）返回要放到前面的分量的掩码，
+/// 超出分量个数的高位忽略。不到两个向量的数组用`pred`逐个判断，`pred`要与`to_mask`一致，
+/// 不一致时划分的结果不确定，但不会越界。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn partition_by_mask<T: SimdElement>(
+    arr: &mut [T],
+    to_mask: impl Fn(T::Vector) -> u16,
+    pred: impl Fn(T) -> bool,
+) -> usize {
+    if arr.len() < 2 * T::LANES {
+        normal::partition_by(arr, |&x| pred(x))
+    } else {
+        unsafe { vector_partition_by_mask(arr, |val| to_mask(val) & T::FULL_MASK) }
+    }
+}
+
+/// `to_mask`返回的掩码中为1的分量放左边，其余的放右边，返回左边的个数
+///
+/// # Safety
+/// - arr.len() >= 2 * T::LANES
+/// - `to_mask`返回的掩码不超过 `T::FULL_MASK`
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn vector_partition_by_mask<T: SimdElement>(
+    arr: &mut [T],
+    to_mask: impl Fn(T::Vector) -> u16,
+) -> usize {
+    let s = T::LANES;
+    let mut left = 0;
+    let mut right = arr.len();
+    unsafe {
+        let left_val = T::loadu(arr.as_ptr().add(left));
         let mut left_w = left;
-        left += S;
+        left += s;
 
         let mut right_w = right;
-        right -= S;
-        let right_val = _mm512_loadu_epi32(arr.as_ptr().offset(right as isize));
+        right -= s;
+        let right_val = T::loadu(arr.as_ptr().add(right));
 
-        while left + S <= right {
+        while left + s <= right {
             let val;
             if left - left_w <= right_w - right {
-                val = _mm512_loadu_epi32(arr.as_ptr().offset(left as isize));
-                left += S;
+                val = T::loadu(arr.as_ptr().add(left));
+                left += s;
             } else {
-                right -= S;
-                val = _mm512_loadu_epi32(arr.as_ptr().offset(right as isize));
+                right -= s;
+                val = T::loadu(arr.as_ptr().add(right));
             }
 
-            let mask = _mm512_cmp_epi32_mask::<_MM_CMPINT_LE>(val, pivotvec);
+            let mask = to_mask(val);
 
             let nb_low = mask.count_ones() as usize;
-            let nb_high = S - nb_low;
+            let nb_high = s - nb_low;
 
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(left_w as isize) as *mut i32 as _,
-                mask,
-                val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(left_w), mask, val);
             left_w += nb_low;
 
             right_w -= nb_high;
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(right_w as isize) as *mut i32 as _,
-                !mask,
-                val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(right_w), !mask, val);
         }
 
         {
             let remaining = right - left;
-            let val = _mm512_loadu_epi32(arr.as_ptr().offset(left as isize));
+            let val = T::loadu(arr.as_ptr().add(left));
             // left = right;
 
-            let mask = _mm512_cmp_epi32_mask::<_MM_CMPINT_LE>(val, pivotvec);
+            let mask = to_mask(val);
 
             let mask_low = mask & !(!0 << remaining);
             let mask_high = !mask & !(!0 << remaining);
@@ -198,303 +589,295 @@ pub unsafe fn vector_partition(arr: &mut [i32], pivot: i32) -> usize {
             let nb_low = mask_low.count_ones() as usize;
             let nb_high = mask_high.count_ones() as usize;
 
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(left_w as isize) as *mut i32 as _,
-                mask_low,
-                val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(left_w), mask_low, val);
             left_w += nb_low;
 
             right_w -= nb_high;
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(right_w as isize) as *mut i32 as _,
-                mask_high,
-                val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(right_w), mask_high, val);
         }
         {
-            let mask = _mm512_cmp_epi32_mask::<_MM_CMPINT_LE>(left_val, pivotvec);
+            let mask = to_mask(left_val);
 
             let nb_low = mask.count_ones() as usize;
-            let nb_high = S - nb_low;
+            let nb_high = s - nb_low;
 
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(left_w as isize) as *mut i32 as _,
-                mask,
-                left_val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(left_w), mask, left_val);
             left_w += nb_low;
 
             right_w -= nb_high;
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(right_w as isize) as *mut i32 as _,
-                !mask,
-                left_val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(right_w), !mask, left_val);
         }
         {
-            let mask = _mm512_cmp_epi32_mask::<_MM_CMPINT_LE>(right_val, pivotvec);
+            let mask = to_mask(right_val);
 
             let nb_low = mask.count_ones() as usize;
-            let nb_high = S - nb_low;
+            let nb_high = s - nb_low;
 
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(left_w as isize) as *mut i32 as _,
-                mask,
-                right_val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(left_w), mask, right_val);
             left_w += nb_low;
 
             right_w -= nb_high;
-            _mm512_mask_compressstoreu_epi32(
-                arr.as_mut_ptr().offset(right_w as isize) as *mut i32 as _,
-                !mask,
-                right_val,
-            );
+            T::mask_compressstoreu(arr.as_mut_ptr().add(right_w), !mask, right_val);
         }
         left_w
     }
 }
 
-#[inline(always)]
-unsafe fn bitonic_sort1_impl(input: &mut __m512i) {
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xAAAA, perm_neigh_max);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(12, 13, 14, 15, 8, 9, 10, 11, 4, 5, 6, 7, 0, 1, 2, 3);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xCCCC, perm_neigh_max);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xAAAA, perm_neigh_max);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xF0F0, perm_neigh_max);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xCCCC, perm_neigh_max);
-    }
+/// 三路划分：`< pivot` 的放左边，`> pivot` 的放右边，中间是与`pivot`相等的元素。
+/// 返回 `(lt, gt)`，`arr[lt..gt]` 都等于`pivot`。与 [`vector_partition`] 一样不处理最后一个元素。
+///
+/// 相等的元素不写回，最后直接用`pivot`填满中间，所以相等指按位相等：
+/// 浮点数的 -0.0 与 +0.0 不算相等，划分到左边。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+/// - arr.len() > 2 * T::LANES
+#[inline]
+#[target_feature(enable = "avx512f")]
+pub unsafe fn vector_partition3<T: SimdElement>(arr: &mut [T], pivot: T) -> (usize, usize) {
+    let s = T::LANES;
+    let ptr = arr.as_mut_ptr();
+    let mut left = 0;
+    let mut right = arr.len() - 1;
     unsafe {
-        let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xAAAA, perm_neigh_max);
+        let pivotvec = T::set1(pivot);
+
+        let left_val = T::loadu(ptr.add(left));
+        let mut left_w = left;
+        left += s;
+
+        let mut right_w = right;
+        right -= s;
+        let right_val = T::loadu(ptr.add(right));
+
+        // 相等的元素被丢弃，写入的总是不超过读出的，两边空出来的位置只会更多
+        while left + s <= right {
+            let val;
+            if left - left_w <= right_w - right {
+                val = T::loadu(ptr.add(left));
+                left += s;
+            } else {
+                right -= s;
+                val = T::loadu(ptr.add(right));
+            }
+            compressstore3::<T>(ptr, &mut left_w, &mut right_w, val, pivotvec, T::FULL_MASK);
+        }
+
+        let remaining = right - left;
+        let val = T::loadu(ptr.add(left));
+        compressstore3::<T>(
+            ptr,
+            &mut left_w,
+            &mut right_w,
+            val,
+            pivotvec,
+            !(!0 << remaining),
+        );
+        compressstore3::<T>(
+            ptr,
+            &mut left_w,
+            &mut right_w,
+            left_val,
+            pivotvec,
+            T::FULL_MASK,
+        );
+        compressstore3::<T>(
+            ptr,
+            &mut left_w,
+            &mut right_w,
+            right_val,
+            pivotvec,
+            T::FULL_MASK,
+        );
+
+        arr.get_unchecked_mut(left_w..right_w).fill(pivot);
+        (left_w, right_w)
     }
+}
+
+/// `valid`中的分量按 小于 / 相等 / 大于 `pivot` 分开，小于的写到`left_w`，大于的写到`right_w`之前
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn compressstore3<T: SimdElement>(
+    ptr: *mut T,
+    left_w: &mut usize,
+    right_w: &mut usize,
+    val: T::Vector,
+    pivotvec: T::Vector,
+    valid: u16,
+) {
+    let le = T::cmp_le_mask(val, pivotvec) & valid;
+    let eq = T::cmp_eq_mask(val, pivotvec) & le;
+    let mask_low = le & !eq;
+    let mask_high = !le & valid;
+
     unsafe {
-        let idx_no_neigh = _mm512_set_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xFF00, perm_neigh_max);
+        T::mask_compressstoreu(ptr.add(*left_w), mask_low, val);
+        *left_w += mask_low.count_ones() as usize;
+
+        *right_w -= mask_high.count_ones() as usize;
+        T::mask_compressstoreu(ptr.add(*right_w), mask_high, val);
     }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(11, 10, 9, 8, 15, 14, 13, 12, 3, 2, 1, 0, 7, 6, 5, 4);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xF0F0, perm_neigh_max);
+}
+
+/// 排序网络中一起做比较交换的一组向量
+pub(crate) trait Network<T: SimdElement> {
+    /// 每个向量与按`idx`重排后的自己比较交换，`mask`中为1的分量取较大值，其余取较小值
+    unsafe fn exchange(&mut self, idx: __m512i, mask: u16);
+
+    /// 第`i`个向量逆序后与第`j`个向量比较交换，较小值放在`i`，较大值放在`j`
+    unsafe fn exchange_reversed(&mut self, i: usize, j: usize);
+
+    /// 第`i`个向量与第`j`个向量按分量比较交换，较小值放在`i`，较大值放在`j`
+    unsafe fn exchange_vectors(&mut self, i: usize, j: usize);
+}
+
+impl<T: SimdElement> Network<T> for [T::Vector] {
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exchange(&mut self, idx: __m512i, mask: u16) {
+        for input in self {
+            let perm_neigh = T::permutexvar(idx, *input);
+            let perm_neigh_min = T::min(*input, perm_neigh);
+            // 两个操作数的顺序与min相同：相等或无序（±0、NaN）时两个分量互换，不会重复
+            let perm_neigh_max = T::max(*input, perm_neigh);
+            *input = T::mask_mov(perm_neigh_min, mask, perm_neigh_max);
+        }
     }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xCCCC, perm_neigh_max);
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exchange_reversed(&mut self, i: usize, j: usize) {
+        let perm_neigh = reverse::<T>(self[i]);
+        self[i] = T::min(perm_neigh, self[j]);
+        self[j] = T::max(self[j], perm_neigh);
     }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let pperm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        *input = _mm512_mask_mov_epi32(pperm_neigh_min, 0xAAAA, perm_neigh_max);
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exchange_vectors(&mut self, i: usize, j: usize) {
+        let (a, b) = (self[i], self[j]);
+        self[i] = T::min(a, b);
+        // 操作数顺序与min相反：相等或无序时两个分量互换，不会重复
+        self[j] = T::max(b, a);
     }
 }
 
-#[inline(always)]
-unsafe fn bitonic_sort2_impl(input: &mut __m512i, input2: &mut __m512i) {
-    unsafe {
+/// 每个向量各自排序
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn bitonic_sort_impl<T: SimdElement, N: Network<T> + ?Sized>(inputs: &mut N) {
+    if T::LANES == 16 {
         let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xAAAA, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xAAAA, perm_neigh_max2);
-    }
-    unsafe {
+        inputs.exchange(idx_no_neigh, 0xAAAA);
+
         let idx_no_neigh = _mm512_set_epi32(12, 13, 14, 15, 8, 9, 10, 11, 4, 5, 6, 7, 0, 1, 2, 3);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xCCCC, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xCCCC, perm_neigh_max2);
-    }
-    unsafe {
+        inputs.exchange(idx_no_neigh, 0xCCCC);
+
         let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xAAAA, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xAAAA, perm_neigh_max2);
-    }
-    unsafe {
+        inputs.exchange(idx_no_neigh, 0xAAAA);
+
         let idx_no_neigh = _mm512_set_epi32(8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xF0F0, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xF0F0, perm_neigh_max2);
-    }
-    unsafe {
+        inputs.exchange(idx_no_neigh, 0xF0F0);
+
         let idx_no_neigh = _mm512_set_epi32(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xCCCC, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xCCCC, perm_neigh_max2);
-    }
-    unsafe {
+        inputs.exchange(idx_no_neigh, 0xCCCC);
+
         let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xAAAA, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xAAAA, perm_neigh_max2);
-    }
-    unsafe {
+        inputs.exchange(idx_no_neigh, 0xAAAA);
+
         let idx_no_neigh = _mm512_set_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xFF00, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xFF00, perm_neigh_max2);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(11, 10, 9, 8, 15, 14, 13, 12, 3, 2, 1, 0, 7, 6, 5, 4);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xF0F0, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xF0F0, perm_neigh_max2);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xCCCC, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xCCCC, perm_neigh_max2);
+        inputs.exchange(idx_no_neigh, 0xFF00);
+    } else {
+        let idx_no_neigh = _mm512_set_epi64(6, 7, 4, 5, 2, 3, 0, 1);
+        inputs.exchange(idx_no_neigh, 0xAA);
+
+        let idx_no_neigh = _mm512_set_epi64(4, 5, 6, 7, 0, 1, 2, 3);
+        inputs.exchange(idx_no_neigh, 0xCC);
+
+        let idx_no_neigh = _mm512_set_epi64(6, 7, 4, 5, 2, 3, 0, 1);
+        inputs.exchange(idx_no_neigh, 0xAA);
+
+        let idx_no_neigh = _mm512_set_epi64(0, 1, 2, 3, 4, 5, 6, 7);
+        inputs.exchange(idx_no_neigh, 0xF0);
     }
-    unsafe {
+    bitonic_clean_impl::<T, N>(inputs, T::LANES / 4);
+}
+
+/// 每个向量都是双调序列，各自整理成有序；从距离为`dist`的比较交换开始
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn bitonic_clean_impl<T: SimdElement, N: Network<T> + ?Sized>(inputs: &mut N, dist: usize) {
+    if T::LANES == 16 {
+        if dist >= 8 {
+            let idx_no_neigh =
+                _mm512_set_epi32(7, 6, 5, 4, 3, 2, 1, 0, 15, 14, 13, 12, 11, 10, 9, 8);
+            inputs.exchange(idx_no_neigh, 0xFF00);
+        }
+        if dist >= 4 {
+            let idx_no_neigh =
+                _mm512_set_epi32(11, 10, 9, 8, 15, 14, 13, 12, 3, 2, 1, 0, 7, 6, 5, 4);
+            inputs.exchange(idx_no_neigh, 0xF0F0);
+        }
+        if dist >= 2 {
+            let idx_no_neigh =
+                _mm512_set_epi32(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2);
+            inputs.exchange(idx_no_neigh, 0xCCCC);
+        }
         let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xAAAA, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xAAAA, perm_neigh_max2);
+        inputs.exchange(idx_no_neigh, 0xAAAA);
+    } else {
+        if dist >= 4 {
+            let idx_no_neigh = _mm512_set_epi64(3, 2, 1, 0, 7, 6, 5, 4);
+            inputs.exchange(idx_no_neigh, 0xF0);
+        }
+        if dist >= 2 {
+            let idx_no_neigh = _mm512_set_epi64(5, 4, 7, 6, 1, 0, 3, 2);
+            inputs.exchange(idx_no_neigh, 0xCC);
+        }
+        let idx_no_neigh = _mm512_set_epi64(6, 7, 4, 5, 2, 3, 0, 1);
+        inputs.exchange(idx_no_neigh, 0xAA);
     }
-    bitonic_sort2_merge_impl(input, input2);
 }
 
-#[inline(always)]
-unsafe fn bitonic_sort2_merge_impl(input: &mut __m512i, input2: &mut __m512i) {
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        *input = _mm512_min_epi32(perm_neigh, *input2);
-        *input2 = _mm512_max_epi32(*input2, perm_neigh);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(7, 6, 5, 4, 3, 2, 1, 0, 15, 14, 13, 12, 11, 10, 9, 8);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xFF00, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xFF00, perm_neigh_max2);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(11, 10, 9, 8, 15, 14, 13, 12, 3, 2, 1, 0, 7, 6, 5, 4);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xF0F0, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xF0F0, perm_neigh_max2);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xCCCC, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xCCCC, perm_neigh_max2);
-    }
-    unsafe {
-        let idx_no_neigh = _mm512_set_epi32(14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1);
-        let perm_neigh = _mm512_permutexvar_epi32(idx_no_neigh, *input);
-        let perm_neigh2 = _mm512_permutexvar_epi32(idx_no_neigh, *input2);
-        let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
-        let perm_neigh_min2 = _mm512_min_epi32(*input2, perm_neigh2);
-        let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
-        let perm_neigh_max2 = _mm512_max_epi32(perm_neigh2, *input2);
-        *input = _mm512_mask_mov_epi32(perm_neigh_min, 0xAAAA, perm_neigh_max);
-        *input2 = _mm512_mask_mov_epi32(perm_neigh_min2, 0xAAAA, perm_neigh_max2);
+/// 向量的分量逆序
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn reverse<T: SimdElement>(input: T::Vector) -> T::Vector {
+    let idx_no_neigh = if T::LANES == 16 {
+        _mm512_set_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15)
+    } else {
+        _mm512_set_epi64(0, 1, 2, 3, 4, 5, 6, 7)
+    };
+    T::permutexvar(idx_no_neigh, input)
+}
+
+/// 各自有序的`n`个向量（`n`是2的幂）逐层两两合并，最后按向量顺序整体有序
+#[inline]
+#[target_feature(enable = "avx512f")]
+pub(crate) unsafe fn bitonic_merge_impl<T: SimdElement, N: Network<T> + ?Sized>(
+    inputs: &mut N,
+    n: usize,
+) {
+    let mut half = 1;
+    while half < n {
+        for group in (0..n).step_by(2 * half) {
+            // 前一半逆序后与后一半比较交换，之后每个向量都是双调序列，且前一半都不大于后一半
+            for i in 0..half {
+                inputs.exchange_reversed(group + i, group + 2 * half - 1 - i);
+            }
+            // 向量之间的半清理
+            let mut dist = half / 2;
+            while dist > 0 {
+                for block in (group..group + 2 * half).step_by(2 * dist) {
+                    for i in block..block + dist {
+                        inputs.exchange_vectors(i, i + dist);
+                    }
+                }
+                dist /= 2;
+            }
+        }
+        bitonic_clean_impl::<T, N>(inputs, T::LANES / 2);
+        half *= 2;
     }
 }
@@ -0,0 +1,364 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use std::cmp::Reverse;
+
+use crate::qsort::SortElement;
+
+/// 可以用AVX-512排序的元素类型，提供排序用到的向量操作
+///
+/// - 32位的元素（`i32`/`u32`/`f32`）一个向量有16个分量
+/// - 64位的元素（`i64`/`u64`/`f64`）一个向量有8个分量
+///
+/// 掩码统一用 `u16` 表示，8个分量时只用低8位。
+pub trait SimdElement: SortElement {
+    /// 一个AVX-512向量：`__m512i`、`__m512` 或 `__m512d`
+    type Vector: Copy;
+
+    /// 向量分量个数
+    const LANES: usize;
+
+    /// 所有分量的掩码
+    #[doc(hidden)]
+    const FULL_MASK: u16 = (u32::MAX >> (32 - Self::LANES)) as u16;
+
+    /// 填充不满一个向量的部分，排序后会在最右边
+    #[doc(hidden)]
+    const PADDING: Self;
+
+    /// 最小的值，降序（[`Reverse`]）排序时用来填充
+    #[doc(hidden)]
+    const LOWEST: Self;
+
+    #[doc(hidden)]
+    unsafe fn set1(x: Self) -> Self::Vector;
+
+    #[doc(hidden)]
+    unsafe fn loadu(ptr: *const Self) -> Self::Vector;
+
+    #[doc(hidden)]
+    unsafe fn mask_loadu(src: Self::Vector, mask: u16, ptr: *const Self) -> Self::Vector;
+
+    #[doc(hidden)]
+    unsafe fn storeu(ptr: *mut Self, v: Self::Vector);
+
+    #[doc(hidden)]
+    unsafe fn mask_compressstoreu(ptr: *mut Self, mask: u16, v: Self::Vector);
+
+    /// `a <= b` 的分量
+    #[doc(hidden)]
+    unsafe fn cmp_le_mask(a: Self::Vector, b: Self::Vector) -> u16;
+
+    /// `a < b` 的分量
+    #[doc(hidden)]
+    unsafe fn cmp_lt_mask(a: Self::Vector, b: Self::Vector) -> u16;
+
+    /// 按位相等的分量（浮点数 -0.0 与 +0.0 不相等，NaN 与相同位模式的 NaN 相等）
+    #[doc(hidden)]
+    unsafe fn cmp_eq_mask(a: Self::Vector, b: Self::Vector) -> u16;
+
+    #[doc(hidden)]
+    unsafe fn min(a: Self::Vector, b: Self::Vector) -> Self::Vector;
+
+    #[doc(hidden)]
+    unsafe fn max(a: Self::Vector, b: Self::Vector) -> Self::Vector;
+
+    #[doc(hidden)]
+    unsafe fn mask_mov(src: Self::Vector, mask: u16, a: Self::Vector) -> Self::Vector;
+
+    /// `idx` 的分量宽度与元素相同
+    #[doc(hidden)]
+    unsafe fn permutexvar(idx: __m512i, v: Self::Vector) -> Self::Vector;
+}
+
+macro_rules! impl_simd_element {
+    (
+        $t:ty as $raw:ty, $vector:ty, $lanes:expr, $padding:expr, $lowest:expr,
+        $mask:ty,
+        set1: $set1:ident,
+        loadu: $loadu:ident,
+        mask_loadu: $mask_loadu:ident,
+        storeu: $storeu:ident,
+        mask_compressstoreu: $mask_compressstoreu:ident,
+        cmp_le_mask: $cmp_le_mask:ident::<$le:ident>,
+        cmp_lt_mask: $cmp_lt_mask:ident::<$lt:ident>,
+        cmp_eq_mask: |$a:ident, $b:ident| $cmp_eq_mask:expr,
+        min: $min:ident,
+        max: $max:ident,
+        mask_mov: $mask_mov:ident,
+        permutexvar: $permutexvar:ident,
+    ) => {
+        impl SimdElement for $t {
+            type Vector = $vector;
+
+            const LANES: usize = $lanes;
+
+            const PADDING: Self = $padding;
+
+            const LOWEST: Self = $lowest;
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn set1(x: Self) -> Self::Vector {
+                $set1(x as $raw)
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn loadu(ptr: *const Self) -> Self::Vector {
+                unsafe { $loadu(ptr.cast()) }
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn mask_loadu(src: Self::Vector, mask: u16, ptr: *const Self) -> Self::Vector {
+                unsafe { $mask_loadu(src, mask as $mask, ptr.cast()) }
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn storeu(ptr: *mut Self, v: Self::Vector) {
+                unsafe { $storeu(ptr.cast(), v) }
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn mask_compressstoreu(ptr: *mut Self, mask: u16, v: Self::Vector) {
+                unsafe { $mask_compressstoreu(ptr.cast(), mask as $mask, v) }
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn cmp_le_mask(a: Self::Vector, b: Self::Vector) -> u16 {
+                $cmp_le_mask::<$le>(a, b) as u16
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn cmp_lt_mask(a: Self::Vector, b: Self::Vector) -> u16 {
+                $cmp_lt_mask::<$lt>(a, b) as u16
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn cmp_eq_mask($a: Self::Vector, $b: Self::Vector) -> u16 {
+                $cmp_eq_mask as u16
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn min(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+                $min(a, b)
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn max(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+                $max(a, b)
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn mask_mov(src: Self::Vector, mask: u16, a: Self::Vector) -> Self::Vector {
+                $mask_mov(src, mask as $mask, a)
+            }
+
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn permutexvar(idx: __m512i, v: Self::Vector) -> Self::Vector {
+                $permutexvar(idx, v)
+            }
+        }
+    };
+}
+
+impl_simd_element! {
+    i32 as i32, __m512i, 16, i32::MAX, i32::MIN,
+    __mmask16,
+    set1: _mm512_set1_epi32,
+    loadu: _mm512_loadu_epi32,
+    mask_loadu: _mm512_mask_loadu_epi32,
+    storeu: _mm512_storeu_epi32,
+    mask_compressstoreu: _mm512_mask_compressstoreu_epi32,
+    cmp_le_mask: _mm512_cmp_epi32_mask::<_MM_CMPINT_LE>,
+    cmp_lt_mask: _mm512_cmp_epi32_mask::<_MM_CMPINT_LT>,
+    cmp_eq_mask: |a, b| _mm512_cmpeq_epi32_mask(a, b),
+    min: _mm512_min_epi32,
+    max: _mm512_max_epi32,
+    mask_mov: _mm512_mask_mov_epi32,
+    permutexvar: _mm512_permutexvar_epi32,
+}
+
+impl_simd_element! {
+    u32 as i32, __m512i, 16, u32::MAX, u32::MIN,
+    __mmask16,
+    set1: _mm512_set1_epi32,
+    loadu: _mm512_loadu_epi32,
+    mask_loadu: _mm512_mask_loadu_epi32,
+    storeu: _mm512_storeu_epi32,
+    mask_compressstoreu: _mm512_mask_compressstoreu_epi32,
+    cmp_le_mask: _mm512_cmp_epu32_mask::<_MM_CMPINT_LE>,
+    cmp_lt_mask: _mm512_cmp_epu32_mask::<_MM_CMPINT_LT>,
+    cmp_eq_mask: |a, b| _mm512_cmpeq_epi32_mask(a, b),
+    min: _mm512_min_epu32,
+    max: _mm512_max_epu32,
+    mask_mov: _mm512_mask_mov_epi32,
+    permutexvar: _mm512_permutexvar_epi32,
+}
+
+impl_simd_element! {
+    i64 as i64, __m512i, 8, i64::MAX, i64::MIN,
+    __mmask8,
+    set1: _mm512_set1_epi64,
+    loadu: _mm512_loadu_epi64,
+    mask_loadu: _mm512_mask_loadu_epi64,
+    storeu: _mm512_storeu_epi64,
+    mask_compressstoreu: _mm512_mask_compressstoreu_epi64,
+    cmp_le_mask: _mm512_cmp_epi64_mask::<_MM_CMPINT_LE>,
+    cmp_lt_mask: _mm512_cmp_epi64_mask::<_MM_CMPINT_LT>,
+    cmp_eq_mask: |a, b| _mm512_cmpeq_epi64_mask(a, b),
+    min: _mm512_min_epi64,
+    max: _mm512_max_epi64,
+    mask_mov: _mm512_mask_mov_epi64,
+    permutexvar: _mm512_permutexvar_epi64,
+}
+
+impl_simd_element! {
+    u64 as i64, __m512i, 8, u64::MAX, u64::MIN,
+    __mmask8,
+    set1: _mm512_set1_epi64,
+    loadu: _mm512_loadu_epi64,
+    mask_loadu: _mm512_mask_loadu_epi64,
+    storeu: _mm512_storeu_epi64,
+    mask_compressstoreu: _mm512_mask_compressstoreu_epi64,
+    cmp_le_mask: _mm512_cmp_epu64_mask::<_MM_CMPINT_LE>,
+    cmp_lt_mask: _mm512_cmp_epu64_mask::<_MM_CMPINT_LT>,
+    cmp_eq_mask: |a, b| _mm512_cmpeq_epi64_mask(a, b),
+    min: _mm512_min_epu64,
+    max: _mm512_max_epu64,
+    mask_mov: _mm512_mask_mov_epi64,
+    permutexvar: _mm512_permutexvar_epi64,
+}
+
+impl_simd_element! {
+    f32 as f32, __m512, 16, f32::INFINITY, f32::NEG_INFINITY,
+    __mmask16,
+    set1: _mm512_set1_ps,
+    loadu: _mm512_loadu_ps,
+    mask_loadu: _mm512_mask_loadu_ps,
+    storeu: _mm512_storeu_ps,
+    mask_compressstoreu: _mm512_mask_compressstoreu_ps,
+    cmp_le_mask: _mm512_cmp_ps_mask::<_CMP_LE_OQ>,
+    cmp_lt_mask: _mm512_cmp_ps_mask::<_CMP_LT_OQ>,
+    cmp_eq_mask: |a, b| _mm512_cmpeq_epi32_mask(_mm512_castps_si512(a), _mm512_castps_si512(b)),
+    min: _mm512_min_ps,
+    max: _mm512_max_ps,
+    mask_mov: _mm512_mask_mov_ps,
+    permutexvar: _mm512_permutexvar_ps,
+}
+
+impl_simd_element! {
+    f64 as f64, __m512d, 8, f64::INFINITY, f64::NEG_INFINITY,
+    __mmask8,
+    set1: _mm512_set1_pd,
+    loadu: _mm512_loadu_pd,
+    mask_loadu: _mm512_mask_loadu_pd,
+    storeu: _mm512_storeu_pd,
+    mask_compressstoreu: _mm512_mask_compressstoreu_pd,
+    cmp_le_mask: _mm512_cmp_pd_mask::<_CMP_LE_OQ>,
+    cmp_lt_mask: _mm512_cmp_pd_mask::<_CMP_LT_OQ>,
+    cmp_eq_mask: |a, b| _mm512_cmpeq_epi64_mask(_mm512_castpd_si512(a), _mm512_castpd_si512(b)),
+    min: _mm512_min_pd,
+    max: _mm512_max_pd,
+    mask_mov: _mm512_mask_mov_pd,
+    permutexvar: _mm512_permutexvar_pd,
+}
+
+/// 降序：比较的方向反过来，`min`与`max`互换，用最小的值填充
+impl<T: SimdElement> SimdElement for Reverse<T>
+where
+    Reverse<T>: SortElement,
+{
+    type Vector = T::Vector;
+
+    const LANES: usize = T::LANES;
+
+    const PADDING: Self = Reverse(T::LOWEST);
+
+    const LOWEST: Self = Reverse(T::PADDING);
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn set1(x: Self) -> Self::Vector {
+        unsafe { T::set1(x.0) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn loadu(ptr: *const Self) -> Self::Vector {
+        unsafe { T::loadu(ptr.cast()) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn mask_loadu(src: Self::Vector, mask: u16, ptr: *const Self) -> Self::Vector {
+        unsafe { T::mask_loadu(src, mask, ptr.cast()) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn storeu(ptr: *mut Self, v: Self::Vector) {
+        unsafe { T::storeu(ptr.cast(), v) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn mask_compressstoreu(ptr: *mut Self, mask: u16, v: Self::Vector) {
+        unsafe { T::mask_compressstoreu(ptr.cast(), mask, v) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn cmp_le_mask(a: Self::Vector, b: Self::Vector) -> u16 {
+        unsafe { T::cmp_le_mask(b, a) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn cmp_lt_mask(a: Self::Vector, b: Self::Vector) -> u16 {
+        unsafe { T::cmp_lt_mask(b, a) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn cmp_eq_mask(a: Self::Vector, b: Self::Vector) -> u16 {
+        unsafe { T::cmp_eq_mask(a, b) }
+    }
+
+    /// 与 `T::min` 一样，相等或无序时取第二个操作数
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn min(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { T::max(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn max(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { T::min(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn mask_mov(src: Self::Vector, mask: u16, a: Self::Vector) -> Self::Vector {
+        unsafe { T::mask_mov(src, mask, a) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn permutexvar(idx: __m512i, v: Self::Vector) -> Self::Vector {
+        unsafe { T::permutexvar(idx, v) }
+    }
+}
@@ -0,0 +1,375 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m512i, _mm512_cmp_epi32_mask, _mm512_cmpeq_epi32_mask, _mm512_loadu_epi32,
+    _mm512_mask_compressstoreu_epi32, _mm512_mask_loadu_epi32, _mm512_mask_mov_epi32,
+    _mm512_max_epi32, _mm512_min_epi32, _mm512_permutexvar_epi32, _mm512_set1_epi32,
+    _mm512_storeu_epi32, _MM_CMPINT_LE, _MM_CMPINT_LT,
+};
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m512i, _mm512_cmp_epi32_mask, _mm512_cmpeq_epi32_mask, _mm512_loadu_epi32,
+    _mm512_mask_compressstoreu_epi32, _mm512_mask_loadu_epi32, _mm512_mask_mov_epi32,
+    _mm512_max_epi32, _mm512_min_epi32, _mm512_permutexvar_epi32, _mm512_set1_epi32,
+    _mm512_storeu_epi32, _MM_CMPINT_LE, _MM_CMPINT_LT,
+};
+
+use std::mem;
+
+use super::{
+    bitonic_merge_impl, bitonic_sort_impl, get_pivot, reverse, Network, DEFAULT_BITONIC_VECTORS,
+};
+use crate::qsort::{depth_limit, heapsort, sort_by_key_with, swap_unchecked};
+
+const S: usize = 16;
+/// 不超过这个长度的子数组直接用双调排序网络排序，与 [`qsort`](super::qsort) 默认的相同
+const CUTOFF: usize = DEFAULT_BITONIC_VECTORS * S;
+
+/// 按`keys`排序，`values`中的元素跟着对应的key一起移动
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `keys.len() != values.len()`
+#[target_feature(enable = "avx512f")]
+pub fn sort_pairs(keys: &mut [i32], values: &mut [u32]) {
+    assert_eq!(keys.len(), values.len());
+    unsafe { qsort_pairs(keys, values, depth_limit(keys.len()), None) }
+}
+
+/// 返回使`keys`有序的下标
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `keys.len() > u32::MAX`
+#[target_feature(enable = "avx512f")]
+pub fn argsort(keys: &[i32]) -> Vec<u32> {
+    let len = u32::try_from(keys.len()).expect("argsort: too many keys");
+    let mut keys = keys.to_vec();
+    let mut indices: Vec<u32> = (0..len).collect();
+    sort_pairs(&mut keys, &mut indices);
+    indices
+}
+
+/// 按`key`返回的`i32`排序（不稳定）：key取到连续的数组里，连同下标用 [`sort_pairs`] 排序，
+/// 再按下标原地重排`arr`
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `arr.len() > u32::MAX`
+#[target_feature(enable = "avx512f")]
+pub fn sort_by_key<T, F: Fn(&T) -> i32>(arr: &mut [T], key: F) {
+    sort_by_key_with(arr, key, |keys, values| sort_pairs(keys, values))
+}
+
+/// 递归深度超过`limit`后改用堆排序；只递归较短的一边，较长的一边继续循环。
+/// `pred`与 [`qsort_impl`](super::qsort_impl) 中的相同。
+///
+/// # Safety
+/// - keys.len() == values.len()
+#[target_feature(enable = "avx512f")]
+unsafe fn qsort_pairs(
+    mut keys: &mut [i32],
+    mut values: &mut [u32],
+    mut limit: u32,
+    mut pred: Option<i32>,
+) {
+    loop {
+        let len = keys.len();
+        match len {
+            ..=1 => (),
+            _ if len <= CUTOFF => unsafe { bitonic_sort_pairs(keys, values) },
+            _ if limit == 0 => heapsort(keys, |keys, a, b| unsafe {
+                swap_unchecked(keys, a, b);
+                swap_unchecked(values, a, b);
+            }),
+            _ => unsafe {
+                limit -= 1;
+                let (lt, gt) = partition_pairs(keys, values, pred);
+                let pivot = *keys.get_unchecked(lt);
+                let (left_keys, right_keys) = mem::take(&mut keys).split_at_mut_unchecked(lt);
+                let right_keys = right_keys.get_unchecked_mut(gt - lt..);
+                let (left_values, right_values) = mem::take(&mut values).split_at_mut_unchecked(lt);
+                let right_values = right_values.get_unchecked_mut(gt - lt..);
+                if left_keys.len() < right_keys.len() {
+                    qsort_pairs(left_keys, left_values, limit, Some(pivot));
+                    (keys, values) = (right_keys, right_values);
+                } else {
+                    qsort_pairs(right_keys, right_values, limit, pred);
+                    (keys, values) = (left_keys, left_values);
+                    pred = Some(pivot);
+                }
+                continue;
+            },
+        }
+        return;
+    }
+}
+
+/// 先把key为`i32::MAX`的元素移到末尾，它们已经在最终位置，
+/// 剩下的按长度选用1、2、4或8个向量的双调排序网络。
+///
+/// 不满的部分用`i32::MAX`填充，key与填充的相同时payload会混在一起，所以要先分出去。
+///
+/// # Safety
+/// - keys.len() <= CUTOFF
+/// - keys.len() == values.len()
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn bitonic_sort_pairs(keys: &mut [i32], values: &mut [u32]) {
+    let mut len = 0;
+    for i in 0..keys.len() {
+        if keys[i] != i32::MAX {
+            keys.swap(len, i);
+            values.swap(len, i);
+            len += 1;
+        }
+    }
+
+    let (keys, values) = (&mut keys[..len], &mut values[..len]);
+    unsafe {
+        match len.div_ceil(S) {
+            0 => (),
+            1 => bitonic_sort_pairs_n::<1>(keys, values),
+            2 => bitonic_sort_pairs_n::<2>(keys, values),
+            3..=4 => bitonic_sort_pairs_n::<4>(keys, values),
+            _ => bitonic_sort_pairs_n::<8>(keys, values),
+        }
+    }
+}
+
+/// 读入`N`个向量排序后写回，不满的部分用`i32::MAX`填充
+///
+/// # Safety
+/// - keys.len() <= N * S
+/// - keys.len() == values.len()
+/// - `keys`中没有`i32::MAX`
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn bitonic_sort_pairs_n<const N: usize>(keys: &mut [i32], values: &mut [u32]) {
+    let len = keys.len();
+    let keys_ptr = keys.as_mut_ptr();
+    let values_ptr = values.as_mut_ptr().cast::<i32>();
+    let full = len / S;
+    let rest = len % S;
+    // `rest == 0` 时不用
+    let mask: u16 = 0xFFFF >> ((S - rest) % S);
+
+    unsafe {
+        let padding = _mm512_set1_epi32(i32::MAX);
+        let mut keys_v = [padding; N];
+        let mut values_v = [padding; N];
+        for i in 0..full {
+            keys_v[i] = _mm512_loadu_epi32(keys_ptr.add(i * S));
+            values_v[i] = _mm512_loadu_epi32(values_ptr.add(i * S));
+        }
+        if rest > 0 {
+            keys_v[full] = _mm512_mask_loadu_epi32(padding, mask, keys_ptr.add(full * S));
+            values_v[full] = _mm512_mask_loadu_epi32(padding, mask, values_ptr.add(full * S));
+        }
+
+        let mut pairs = Pairs {
+            keys: &mut keys_v,
+            values: &mut values_v,
+        };
+        bitonic_sort_impl::<i32, _>(&mut pairs);
+        bitonic_merge_impl::<i32, _>(&mut pairs, N);
+
+        for i in 0..full {
+            _mm512_storeu_epi32(keys_ptr.add(i * S), keys_v[i]);
+            _mm512_storeu_epi32(values_ptr.add(i * S), values_v[i]);
+        }
+        if rest > 0 {
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(full * S), mask, keys_v[full]);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(full * S), mask, values_v[full]);
+        }
+    }
+}
+
+/// 划分后 `keys[lt..gt]` 都等于枢轴，已在最终位置，返回 `(lt, gt)`。
+///
+/// 选出的枢轴不小于`pred`时，它就是这一段的最大值，只把小于它的元素分到左边，
+/// 右边都等于枢轴，不用再排序；大量重复的key这样一次分出去。
+///
+/// # Safety
+/// - keys.len() > 2*S
+/// - keys.len() == values.len()
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn partition_pairs(
+    keys: &mut [i32],
+    values: &mut [u32],
+    pred: Option<i32>,
+) -> (usize, usize) {
+    unsafe {
+        let len = keys.len();
+        let (idx, pivot) = get_pivot(keys);
+        swap_unchecked(keys, idx, len - 1);
+        swap_unchecked(values, idx, len - 1);
+        let all_equal = pred.is_some_and(|pred| pred <= pivot);
+        let part = if all_equal {
+            vector_partition_pairs::<_MM_CMPINT_LT>(keys, values, pivot)
+        } else {
+            vector_partition_pairs::<_MM_CMPINT_LE>(keys, values, pivot)
+        };
+        swap_unchecked(keys, part, len - 1);
+        swap_unchecked(values, part, len - 1);
+        (part, if all_equal { len } else { part + 1 })
+    }
+}
+
+/// 与 [`vector_partition`](super::vector_partition) 相同，`values`跟着`keys`一起写入；
+/// `CMP`为真的key分到左边
+///
+/// # Safety
+/// - keys.len() > 2*S
+/// - keys.len() == values.len()
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn vector_partition_pairs<const CMP: i32>(
+    keys: &mut [i32],
+    values: &mut [u32],
+    pivot: i32,
+) -> usize {
+    let keys_ptr = keys.as_mut_ptr();
+    let values_ptr = values.as_mut_ptr().cast::<i32>();
+    let mut left = 0;
+    let mut right = keys.len() - 1;
+    unsafe {
+        let pivotvec = _mm512_set1_epi32(pivot);
+
+        let left_val = _mm512_loadu_epi32(keys_ptr.add(left));
+        let left_values = _mm512_loadu_epi32(values_ptr.add(left));
+        let mut left_w = left;
+        left += S;
+
+        let mut right_w = right;
+        right -= S;
+        let right_val = _mm512_loadu_epi32(keys_ptr.add(right));
+        let right_values = _mm512_loadu_epi32(values_ptr.add(right));
+
+        while left + S <= right {
+            let val;
+            let values;
+            if left - left_w <= right_w - right {
+                val = _mm512_loadu_epi32(keys_ptr.add(left));
+                values = _mm512_loadu_epi32(values_ptr.add(left));
+                left += S;
+            } else {
+                right -= S;
+                val = _mm512_loadu_epi32(keys_ptr.add(right));
+                values = _mm512_loadu_epi32(values_ptr.add(right));
+            }
+
+            let mask = _mm512_cmp_epi32_mask::<CMP>(val, pivotvec);
+
+            let nb_low = mask.count_ones() as usize;
+            let nb_high = S - nb_low;
+
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(left_w), mask, val);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(left_w), mask, values);
+            left_w += nb_low;
+
+            right_w -= nb_high;
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(right_w), !mask, val);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(right_w), !mask, values);
+        }
+
+        {
+            let remaining = right - left;
+            let val = _mm512_loadu_epi32(keys_ptr.add(left));
+            let values = _mm512_loadu_epi32(values_ptr.add(left));
+
+            let mask = _mm512_cmp_epi32_mask::<CMP>(val, pivotvec);
+
+            let mask_low = mask & !(!0 << remaining);
+            let mask_high = !mask & !(!0 << remaining);
+
+            let nb_low = mask_low.count_ones() as usize;
+            let nb_high = mask_high.count_ones() as usize;
+
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(left_w), mask_low, val);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(left_w), mask_low, values);
+            left_w += nb_low;
+
+            right_w -= nb_high;
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(right_w), mask_high, val);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(right_w), mask_high, values);
+        }
+        for (val, values) in [(left_val, left_values), (right_val, right_values)] {
+            let mask = _mm512_cmp_epi32_mask::<CMP>(val, pivotvec);
+
+            let nb_low = mask.count_ones() as usize;
+            let nb_high = S - nb_low;
+
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(left_w), mask, val);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(left_w), mask, values);
+            left_w += nb_low;
+
+            right_w -= nb_high;
+            _mm512_mask_compressstoreu_epi32(keys_ptr.add(right_w), !mask, val);
+            _mm512_mask_compressstoreu_epi32(values_ptr.add(right_w), !mask, values);
+        }
+        left_w
+    }
+}
+
+/// 带着payload的key向量，比较交换key时payload跟着一起移动
+struct Pairs<'a> {
+    keys: &'a mut [__m512i],
+    values: &'a mut [__m512i],
+}
+
+impl Network<i32> for Pairs<'_> {
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exchange(&mut self, idx: __m512i, mask: u16) {
+        for (input, values) in self.keys.iter_mut().zip(self.values.iter_mut()) {
+            let perm_neigh = _mm512_permutexvar_epi32(idx, *input);
+            let perm_neigh_values = _mm512_permutexvar_epi32(idx, *values);
+            let perm_neigh_min = _mm512_min_epi32(*input, perm_neigh);
+            let perm_neigh_max = _mm512_max_epi32(perm_neigh, *input);
+            let input_copy = *input;
+            *input = _mm512_mask_mov_epi32(perm_neigh_min, mask, perm_neigh_max);
+
+            // key没有变化的分量保留原来的payload，其余的换成重排后的payload
+            let mask_values = _mm512_cmpeq_epi32_mask(*input, input_copy);
+            *values = _mm512_mask_mov_epi32(perm_neigh_values, mask_values, *values);
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exchange_reversed(&mut self, i: usize, j: usize) {
+        let perm_neigh = reverse::<i32>(self.keys[i]);
+        let perm_neigh_values = reverse::<i32>(self.values[i]);
+        let perm_neigh_min = _mm512_min_epi32(perm_neigh, self.keys[j]);
+        let perm_neigh_max = _mm512_max_epi32(self.keys[j], perm_neigh);
+
+        // 较小值来自第`i`个向量的分量
+        let mask = _mm512_cmpeq_epi32_mask(perm_neigh_min, perm_neigh);
+        self.values[i] = _mm512_mask_mov_epi32(self.values[j], mask, perm_neigh_values);
+        self.values[j] = _mm512_mask_mov_epi32(perm_neigh_values, mask, self.values[j]);
+        self.keys[i] = perm_neigh_min;
+        self.keys[j] = perm_neigh_max;
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exchange_vectors(&mut self, i: usize, j: usize) {
+        let (a, b) = (self.keys[i], self.keys[j]);
+        self.keys[i] = _mm512_min_epi32(a, b);
+        self.keys[j] = _mm512_max_epi32(a, b);
+
+        // 较小值来自第`i`个向量的分量
+        let mask = _mm512_cmpeq_epi32_mask(self.keys[i], a);
+        let (a_values, b_values) = (self.values[i], self.values[j]);
+        self.values[i] = _mm512_mask_mov_epi32(b_values, mask, a_values);
+        self.values[j] = _mm512_mask_mov_epi32(a_values, mask, b_values);
+    }
+}
@@ -0,0 +1,336 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m256i, _mm256_blend_epi32, _mm256_blendv_epi8, _mm256_castsi256_ps, _mm256_cmpeq_epi32,
+    _mm256_cmpgt_epi32, _mm256_loadu_si256, _mm256_maskload_epi32, _mm256_maskstore_epi32,
+    _mm256_max_epi32, _mm256_min_epi32, _mm256_movemask_ps, _mm256_permutevar8x32_epi32,
+    _mm256_set1_epi32, _mm256_set_epi32, _mm256_setr_epi32, _mm256_storeu_si256,
+};
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m256i, _mm256_blend_epi32, _mm256_blendv_epi8, _mm256_castsi256_ps, _mm256_cmpeq_epi32,
+    _mm256_cmpgt_epi32, _mm256_loadu_si256, _mm256_maskload_epi32, _mm256_maskstore_epi32,
+    _mm256_max_epi32, _mm256_min_epi32, _mm256_movemask_ps, _mm256_permutevar8x32_epi32,
+    _mm256_set1_epi32, _mm256_set_epi32, _mm256_setr_epi32, _mm256_storeu_si256,
+};
+
+use std::mem;
+
+use super::{depth_limit, heapsort, swap_unchecked};
+
+const S: usize = 8;
+
+/// AVX2没有 `compressstore`，用查表得到的重排代替：
+/// 第`mask`项把`mask`中为1的分量按原来的顺序排在前面，其余的排在后面
+static PERM_TABLE: [[i32; S]; 256] = perm_table();
+
+const fn perm_table() -> [[i32; S]; 256] {
+    let mut table = [[0; S]; 256];
+    let mut mask = 0;
+    while mask < 256 {
+        let mut n = 0;
+        let mut i = 0;
+        while i < S {
+            if mask >> i & 1 == 1 {
+                table[mask][n] = i as i32;
+                n += 1;
+            }
+            i += 1;
+        }
+        i = 0;
+        while i < S {
+            if mask >> i & 1 == 0 {
+                table[mask][n] = i as i32;
+                n += 1;
+            }
+            i += 1;
+        }
+        mask += 1;
+    }
+    table
+}
+
+/// 与 [`qsort::avx::qsort`](super::avx) 相同的算法，只用AVX2：一个向量8个分量
+///
+/// # Safety
+/// - CPU需要支持 `avx2`
+#[target_feature(enable = "avx2")]
+pub fn qsort(arr: &mut [i32]) {
+    qsort_impl(arr, depth_limit(arr.len()), None);
+}
+
+/// 递归深度超过`limit`后改用堆排序；只递归较短的一边，较长的一边继续循环。
+/// `pred`是上层的枢轴，见 [`partition`]。
+#[target_feature(enable = "avx2")]
+fn qsort_impl(mut arr: &mut [i32], mut limit: u32, mut pred: Option<i32>) {
+    loop {
+        let len = arr.len();
+        match len {
+            ..=1 => (),
+            _ if len <= S => unsafe {
+                let ptr = arr.as_mut_ptr();
+                let mask = first_lanes(len);
+                let mut inputs = [load_padded(ptr, mask)];
+                bitonic_sort_impl(&mut inputs);
+                _mm256_maskstore_epi32(ptr, mask, inputs[0]);
+            },
+            _ if len <= 2 * S => unsafe {
+                let ptr = arr.as_mut_ptr();
+                let mask = first_lanes(len - S);
+                let mut inputs = [
+                    _mm256_loadu_si256(ptr.cast()),
+                    load_padded(ptr.add(S), mask),
+                ];
+                bitonic_sort_impl(&mut inputs);
+                bitonic_sort2_merge_impl(&mut inputs);
+                _mm256_storeu_si256(ptr.cast(), inputs[0]);
+                _mm256_maskstore_epi32(ptr.add(S), mask, inputs[1]);
+            },
+            _ if limit == 0 => heapsort(arr, |v, a, b| unsafe { swap_unchecked(v, a, b) }),
+            _ => unsafe {
+                limit -= 1;
+                let (lt, gt) = partition(arr, pred);
+                let pivot = *arr.get_unchecked(lt);
+                let (left, right) = mem::take(&mut arr).split_at_mut_unchecked(lt);
+                let right = right.get_unchecked_mut(gt - lt..);
+                if left.len() < right.len() {
+                    qsort_impl(left, limit, Some(pivot));
+                    arr = right;
+                } else {
+                    qsort_impl(right, limit, pred);
+                    arr = left;
+                    pred = Some(pivot);
+                }
+                continue;
+            },
+        }
+        return;
+    }
+}
+
+/// 前`n`个分量为全1的掩码，用于 `maskload`/`maskstore`
+#[inline]
+#[target_feature(enable = "avx2")]
+fn first_lanes(n: usize) -> __m256i {
+    _mm256_cmpgt_epi32(
+        _mm256_set1_epi32(n as i32),
+        _mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7),
+    )
+}
+
+/// 读取`mask`中的分量，其余的用`i32::MAX`填充，排序后在最右边
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn load_padded(ptr: *const i32, mask: __m256i) -> __m256i {
+    let input = unsafe { _mm256_maskload_epi32(ptr, mask) };
+    _mm256_blendv_epi8(_mm256_set1_epi32(i32::MAX), input, mask)
+}
+
+/// 在 1/4、1/2、3/4 处三者取中
+#[inline(always)]
+unsafe fn get_pivot(arr: &[i32]) -> (usize, i32) {
+    let len = arr.len();
+    let (i, j, k) = (len / 4, len / 2, len / 4 * 3);
+    let (a, b, c) = unsafe {
+        (
+            *arr.get_unchecked(i),
+            *arr.get_unchecked(j),
+            *arr.get_unchecked(k),
+        )
+    };
+    if (a <= b) == (b <= c) {
+        (j, b)
+    } else if (b <= a) == (a <= c) {
+        (i, a)
+    } else {
+        (k, c)
+    }
+}
+
+/// 划分后 `arr[lt..gt]` 都等于枢轴，已在最终位置，返回 `(lt, gt)`。
+///
+/// `pred`是上层的枢轴，这一段的元素都不大于它；选出的枢轴等于`pred`说明有重复的值，
+/// 改用三路划分把相等的元素一次分出来。
+#[inline]
+#[target_feature(enable = "avx2")]
+fn partition(arr: &mut [i32], pred: Option<i32>) -> (usize, usize) {
+    unsafe {
+        let len = arr.len();
+        let (idx, pivot) = get_pivot(arr);
+        swap_unchecked(arr, idx, len - 1);
+        let three_way = pred.is_some_and(|pred| pred <= pivot);
+        let (lt, gt) = vector_partition(arr, pivot, three_way);
+        swap_unchecked(arr, gt, len - 1);
+        (lt, gt + 1)
+    }
+}
+
+/// 划分 `arr[..len - 1]`：小于等于`pivot`的放左边，大于的放右边，返回 `(lt, gt)`。
+///
+/// `three_way`时与`pivot`相等的元素不写回，最后用`pivot`填满 `arr[lt..gt]`；否则 `lt == gt`。
+///
+/// # Safety
+/// - arr.len() > 2 * S
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn vector_partition(arr: &mut [i32], pivot: i32, three_way: bool) -> (usize, usize) {
+    let ptr = arr.as_mut_ptr();
+    let mut left = 0;
+    let mut right = arr.len() - 1;
+    unsafe {
+        let pivotvec = _mm256_set1_epi32(pivot);
+        let split = |val: __m256i, valid: u8| {
+            let gt = movemask(_mm256_cmpgt_epi32(val, pivotvec));
+            let eq = if three_way {
+                movemask(_mm256_cmpeq_epi32(val, pivotvec))
+            } else {
+                0
+            };
+            (!gt & !eq & valid, gt & valid)
+        };
+
+        let left_val = _mm256_loadu_si256(ptr.add(left).cast());
+        let mut left_w = left;
+        left += S;
+
+        let mut right_w = right;
+        right -= S;
+        let right_val = _mm256_loadu_si256(ptr.add(right).cast());
+
+        // 两边空出来的位置都至少有S个，可以整个向量写入
+        while left + S <= right {
+            let val;
+            if left - left_w <= right_w - right {
+                val = _mm256_loadu_si256(ptr.add(left).cast());
+                left += S;
+            } else {
+                right -= S;
+                val = _mm256_loadu_si256(ptr.add(right).cast());
+            }
+
+            let (low, high) = split(val, 0xFF);
+            let perm_low = permute(val, low);
+            _mm256_storeu_si256(ptr.add(left_w).cast(), perm_low);
+            left_w += low.count_ones() as usize;
+
+            // 大于的排在向量最后，写到`right_w`之前
+            let perm_high = if low | high == 0xFF {
+                perm_low
+            } else {
+                permute(val, !high)
+            };
+            _mm256_storeu_si256(ptr.add(right_w - S).cast(), perm_high);
+            right_w -= high.count_ones() as usize;
+        }
+
+        // 剩下的空位可能不够一个向量，只写有效的分量
+        let remaining = right - left;
+        let val = _mm256_loadu_si256(ptr.add(left).cast());
+        let (low, high) = split(val, !(!0 << remaining));
+        maskstore_partitioned(ptr, &mut left_w, &mut right_w, val, low, high);
+
+        let (low, high) = split(left_val, 0xFF);
+        maskstore_partitioned(ptr, &mut left_w, &mut right_w, left_val, low, high);
+
+        let (low, high) = split(right_val, 0xFF);
+        maskstore_partitioned(ptr, &mut left_w, &mut right_w, right_val, low, high);
+
+        arr.get_unchecked_mut(left_w..right_w).fill(pivot);
+        (left_w, right_w)
+    }
+}
+
+/// `low`中的分量按顺序写到`left_w`，`high`中的写到`right_w`之前
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn maskstore_partitioned(
+    ptr: *mut i32,
+    left_w: &mut usize,
+    right_w: &mut usize,
+    val: __m256i,
+    low: u8,
+    high: u8,
+) {
+    let nb_low = low.count_ones() as usize;
+    let nb_high = high.count_ones() as usize;
+    unsafe {
+        _mm256_maskstore_epi32(ptr.add(*left_w), first_lanes(nb_low), permute(val, low));
+        *left_w += nb_low;
+
+        *right_w -= nb_high;
+        _mm256_maskstore_epi32(ptr.add(*right_w), first_lanes(nb_high), permute(val, high));
+    }
+}
+
+/// 把`mask`中的分量按顺序排在前面
+#[inline]
+#[target_feature(enable = "avx2")]
+pub(crate) fn permute(val: __m256i, mask: u8) -> __m256i {
+    let idx = unsafe { _mm256_loadu_si256(PERM_TABLE[mask as usize].as_ptr().cast()) };
+    _mm256_permutevar8x32_epi32(val, idx)
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+pub(crate) fn movemask(mask: __m256i) -> u8 {
+    _mm256_movemask_ps(_mm256_castsi256_ps(mask)) as u8
+}
+
+/// 每个向量与按`idx`重排后的自己比较交换，`MASK`中为1的分量取较大值，其余取较小值
+#[inline]
+#[target_feature(enable = "avx2")]
+fn exchange<const MASK: i32>(inputs: &mut [__m256i], idx: __m256i) {
+    for input in inputs {
+        let perm_neigh = _mm256_permutevar8x32_epi32(*input, idx);
+        let perm_neigh_min = _mm256_min_epi32(*input, perm_neigh);
+        let perm_neigh_max = _mm256_max_epi32(*input, perm_neigh);
+        *input = _mm256_blend_epi32::<MASK>(perm_neigh_min, perm_neigh_max);
+    }
+}
+
+/// 每个向量各自排序
+#[inline]
+#[target_feature(enable = "avx2")]
+fn bitonic_sort_impl(inputs: &mut [__m256i]) {
+    let idx_no_neigh = _mm256_set_epi32(6, 7, 4, 5, 2, 3, 0, 1);
+    exchange::<0xAA>(inputs, idx_no_neigh);
+
+    let idx_no_neigh = _mm256_set_epi32(4, 5, 6, 7, 0, 1, 2, 3);
+    exchange::<0xCC>(inputs, idx_no_neigh);
+
+    let idx_no_neigh = _mm256_set_epi32(6, 7, 4, 5, 2, 3, 0, 1);
+    exchange::<0xAA>(inputs, idx_no_neigh);
+
+    let idx_no_neigh = _mm256_set_epi32(0, 1, 2, 3, 4, 5, 6, 7);
+    exchange::<0xF0>(inputs, idx_no_neigh);
+
+    bitonic_clean_impl::<false>(inputs);
+}
+
+/// 每个向量都是双调序列，各自整理成有序；`DIST4`时从距离为4的比较交换开始，否则从2开始
+#[inline]
+#[target_feature(enable = "avx2")]
+fn bitonic_clean_impl<const DIST4: bool>(inputs: &mut [__m256i]) {
+    if DIST4 {
+        let idx_no_neigh = _mm256_set_epi32(3, 2, 1, 0, 7, 6, 5, 4);
+        exchange::<0xF0>(inputs, idx_no_neigh);
+    }
+
+    let idx_no_neigh = _mm256_set_epi32(5, 4, 7, 6, 1, 0, 3, 2);
+    exchange::<0xCC>(inputs, idx_no_neigh);
+
+    let idx_no_neigh = _mm256_set_epi32(6, 7, 4, 5, 2, 3, 0, 1);
+    exchange::<0xAA>(inputs, idx_no_neigh);
+}
+
+/// 两个各自有序的向量合并，第一个向量得到较小的一半
+#[inline]
+#[target_feature(enable = "avx2")]
+fn bitonic_sort2_merge_impl(inputs: &mut [__m256i; 2]) {
+    let idx_reverse = _mm256_set_epi32(0, 1, 2, 3, 4, 5, 6, 7);
+    let perm_neigh = _mm256_permutevar8x32_epi32(inputs[0], idx_reverse);
+    inputs[0] = _mm256_min_epi32(perm_neigh, inputs[1]);
+    inputs[1] = _mm256_max_epi32(inputs[1], perm_neigh);
+
+    bitonic_clean_impl::<true>(inputs);
+}
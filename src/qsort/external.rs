@@ -0,0 +1,299 @@
+use std::{
+    env, fs,
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind, Read, Write},
+    mem,
+    path::{Path, PathBuf},
+    process, slice,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{sort, RadixElement};
+
+/// 合并时每一路缓冲区的最小字节数，内存预算更小时减少每一趟合并的路数
+const MIN_BUF_BYTES: usize = 4096;
+/// 每一趟最多合并的路数，避免同时打开太多文件
+const MAX_FAN_IN: usize = 256;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// 可以外部排序的定长记录：`i32`、`u32`、`i64`、`u64`，按本机字节序直接读写文件
+///
+/// # Safety
+/// 记录和文件之间按原始字节拷贝，实现的类型必须没有填充字节，并且任意字节序列都是合法的值。
+/// 这个trait是封闭的，只有上面的整数类型实现了它。
+pub unsafe trait Record: RadixElement + Default + sealed::Sealed {}
+
+macro_rules! impl_record {
+    ($($t:ty),*) => {
+        $(
+        impl sealed::Sealed for $t {}
+        unsafe impl Record for $t {}
+        )*
+    };
+}
+
+impl_record!(i32, u32, i64, u64);
+
+/// 外部排序的参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSortConfig {
+    /// 内存预算（字节），决定每个顺串的长度和合并时缓冲区的大小。
+    /// 顺串开头大部分已经有序时，[`sort`] 合并要用额外的缓冲区，最多是预算的一半
+    pub memory: usize,
+    /// 存放临时顺串文件的目录
+    pub tmp_dir: PathBuf,
+}
+
+impl Default for ExternalSortConfig {
+    /// 内存预算为 256 MiB，临时文件放在 `env::temp_dir`
+    fn default() -> Self {
+        Self {
+            memory: 256 << 20,
+            tmp_dir: env::temp_dir(),
+        }
+    }
+}
+
+/// 对`input`中的定长记录（本机字节序的 `i32`、`u32`、`i64`、`u64`）升序排序，结果写到`output`。
+///
+/// 每次读入内存预算能放下的记录，用 [`sort`] 排好后写到临时文件，
+/// 再用败者树多路合并这些顺串；顺串太多时分几趟合并。只有一个顺串时直接写到`output`。
+///
+/// # Errors
+/// - 读写文件失败
+/// - `input`的长度不是记录大小的整数倍（[`ErrorKind::InvalidData`]）
+pub fn external_sort<T: Record>(
+    input: &Path,
+    output: &Path,
+    config: &ExternalSortConfig,
+) -> io::Result<()> {
+    let size = mem::size_of::<T>();
+    let memory = config.memory.max(2 * MIN_BUF_BYTES);
+    let mut input = File::open(input)?;
+
+    // 生成顺串
+    let chunk_len = memory / size;
+    let mut buf = vec![T::default(); chunk_len];
+    let mut runs = Vec::new();
+    loop {
+        let len = read_records(&mut input, &mut buf)?;
+        if len == 0 && !runs.is_empty() {
+            break;
+        }
+        let chunk = &mut buf[..len];
+        sort(chunk);
+
+        if runs.is_empty() && len < chunk_len {
+            // 整个文件都在内存里
+            return write_records(&mut File::create(output)?, chunk);
+        }
+        let (run, mut file) = TempRun::create(&config.tmp_dir)?;
+        write_records(&mut file, chunk)?;
+        runs.push(run);
+        if len < chunk_len {
+            break;
+        }
+    }
+    drop(buf);
+
+    // 路数受内存预算限制，每一路至少 `MIN_BUF_BYTES`，输出也占一份
+    let fan_in = (memory / MIN_BUF_BYTES - 1).clamp(2, MAX_FAN_IN);
+    let buf_len = memory / (fan_in + 1) / size;
+    while runs.len() > fan_in {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(fan_in));
+        for group in runs.chunks(fan_in) {
+            let (run, file) = TempRun::create(&config.tmp_dir)?;
+            merge_runs::<T>(group, file, buf_len)?;
+            merged.push(run);
+        }
+        runs = merged;
+    }
+    merge_runs::<T>(&runs, File::create(output)?, buf_len)
+}
+
+/// 临时的顺串文件，drop时删除
+struct TempRun {
+    path: PathBuf,
+}
+
+impl TempRun {
+    /// 在`dir`中新建一个顺串文件。
+    ///
+    /// 文件名可以猜到，所以只新建、不打开已有的文件（也不跟随符号链接），
+    /// 重名时换一个名字；在 Unix 上只有当前用户可以读写。
+    fn create(dir: &Path) -> io::Result<(Self, File)> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        loop {
+            let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = dir.join(format!("simd_demo-{}-{id}.run", process::id()));
+            match options.open(&path) {
+                Ok(file) => return Ok((Self { path }, file)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for TempRun {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 把有序的`runs`合并到`output`，每一路和输出各用`buf_len`个元素的缓冲区
+fn merge_runs<T: Record>(runs: &[TempRun], mut output: File, buf_len: usize) -> io::Result<()> {
+    let mut readers = runs
+        .iter()
+        .map(|run| Ok(RunReader::new(File::open(&run.path)?, buf_len)))
+        .collect::<io::Result<Vec<RunReader<T>>>>()?;
+    let heads = readers
+        .iter_mut()
+        .map(RunReader::next)
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut out = Vec::with_capacity(buf_len);
+    let mut tree = LoserTree::new(heads);
+    while let Some((i, x)) = tree.winner() {
+        out.push(x);
+        if out.len() == buf_len {
+            write_records(&mut output, &out)?;
+            out.clear();
+        }
+        tree.replace(i, readers[i].next()?);
+    }
+    write_records(&mut output, &out)
+}
+
+/// 按块读入一个顺串
+struct RunReader<T> {
+    file: File,
+    buf: Vec<T>,
+    pos: usize,
+    len: usize,
+}
+
+impl<T: Record> RunReader<T> {
+    fn new(file: File, buf_len: usize) -> Self {
+        Self {
+            file,
+            buf: vec![T::default(); buf_len.max(1)],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    fn next(&mut self) -> io::Result<Option<T>> {
+        if self.pos == self.len {
+            self.len = read_records(&mut self.file, &mut self.buf)?;
+            self.pos = 0;
+            if self.len == 0 {
+                return Ok(None);
+            }
+        }
+        self.pos += 1;
+        Ok(Some(self.buf[self.pos - 1]))
+    }
+}
+
+/// 败者树：`tree[0]`是胜者（最小的一路），`tree[1..k]`是各内部节点上的败者，
+/// 第`i`路是第`k + i`个节点。读完的一路当作无穷大。
+struct LoserTree<T> {
+    tree: Vec<usize>,
+    heads: Vec<Option<T>>,
+}
+
+impl<T: Copy + PartialOrd> LoserTree<T> {
+    fn new(heads: Vec<Option<T>>) -> Self {
+        let mut tree = Self {
+            tree: vec![0; heads.len()],
+            heads,
+        };
+        if tree.heads.len() > 1 {
+            tree.tree[0] = tree.build(1);
+        }
+        tree
+    }
+
+    /// 建立以`node`为根的子树，返回子树的胜者
+    fn build(&mut self, node: usize) -> usize {
+        let k = self.heads.len();
+        if node >= k {
+            return node - k;
+        }
+        let left = self.build(2 * node);
+        let right = self.build(2 * node + 1);
+        let (winner, loser) = if self.beats(left, right) {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        self.tree[node] = loser;
+        winner
+    }
+
+    fn beats(&self, a: usize, b: usize) -> bool {
+        match (self.heads[a], self.heads[b]) {
+            (Some(x), Some(y)) => x < y,
+            (x, _) => x.is_some(),
+        }
+    }
+
+    /// 当前最小的一路和它的元素，所有路都读完时为 `None`
+    fn winner(&self) -> Option<(usize, T)> {
+        let i = self.tree[0];
+        self.heads[i].map(|x| (i, x))
+    }
+
+    /// 第`i`路的下一个元素换成`head`，从叶子到根重新比赛
+    fn replace(&mut self, i: usize, head: Option<T>) {
+        self.heads[i] = head;
+        let mut winner = i;
+        let mut node = (i + self.heads.len()) / 2;
+        while node > 0 {
+            if self.beats(self.tree[node], winner) {
+                mem::swap(&mut self.tree[node], &mut winner);
+            }
+            node /= 2;
+        }
+        self.tree[0] = winner;
+    }
+}
+
+/// 读满`buf`或读到文件末尾，返回读到的记录数
+fn read_records<T: Record>(reader: &mut impl Read, buf: &mut [T]) -> io::Result<usize> {
+    // `Record`没有填充字节，任意字节都是合法的值
+    let bytes =
+        unsafe { slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), mem::size_of_val(buf)) };
+    let mut filled = 0;
+    while filled < bytes.len() {
+        match reader.read(&mut bytes[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    if filled % mem::size_of::<T>() != 0 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "文件长度不是记录大小的整数倍",
+        ));
+    }
+    Ok(filled / mem::size_of::<T>())
+}
+
+fn write_records<T: Record>(writer: &mut impl Write, records: &[T]) -> io::Result<()> {
+    // `Record`没有填充字节，每个字节都已初始化
+    let bytes =
+        unsafe { slice::from_raw_parts(records.as_ptr().cast::<u8>(), mem::size_of_val(records)) };
+    writer.write_all(bytes)
+}
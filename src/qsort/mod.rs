@@ -1,7 +1,458 @@
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx512f",
-))]
+use std::{cmp::Reverse, sync::OnceLock};
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
 /// [A Novel Hybrid Quicksort Algorithm Vectorized using AVX-512 on Intel Skylake](http://arxiv.org/pdf/1704.08579)
 pub mod avx;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub mod avx2;
+/// 超过内存的文件的外部排序
+pub mod external;
 pub mod normal;
+mod parallel;
+pub mod radix;
+
+pub use external::{external_sort, ExternalSortConfig, Record};
+pub use parallel::ParallelConfig;
+pub use radix::RadixElement;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// 可以排序的元素类型：`i32`、`u32`、`i64`、`u64`、`f32`、`f64`，
+/// 以及按降序排序的 `Reverse<i32>` 等（见 [`sort_desc`]）
+///
+/// 浮点数按 `PartialOrd` 比较，含NaN时结果的顺序不确定，需要确定的顺序请用 [`sort_total`]。
+pub trait SortElement: Copy + PartialOrd + sealed::Sealed {
+    #[doc(hidden)]
+    fn dispatch_sort(arr: &mut [Self]);
+
+    #[doc(hidden)]
+    fn dispatch_partial_sort(arr: &mut [Self], k: usize);
+
+    #[doc(hidden)]
+    fn dispatch_select_nth_unstable(arr: &mut [Self], k: usize);
+
+    #[doc(hidden)]
+    fn dispatch_par_sort(arr: &mut [Self], config: &ParallelConfig);
+
+    #[doc(hidden)]
+    fn dispatch_is_sorted(arr: &[Self]) -> bool;
+
+    #[doc(hidden)]
+    fn dispatch_partition_le(arr: &mut [Self], pivot: Self) -> usize;
+
+    #[doc(hidden)]
+    fn dispatch_partition_lt(arr: &mut [Self], pivot: Self) -> usize;
+
+    #[doc(hidden)]
+    fn dispatch_partition_between(arr: &mut [Self], low: Self, high: Self) -> usize;
+}
+
+macro_rules! impl_sort_element {
+    ($($t:ty $(=> $avx2:path)?),*) => {$(
+        impl sealed::Sealed for $t {}
+
+        impl SortElement for $t {
+            fn dispatch_sort(arr: &mut [Self]) {
+                static SORT: OnceLock<fn(&mut [$t])> = OnceLock::new();
+
+                let sort = SORT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr| unsafe { avx::qsort(arr) };
+                    }
+
+                    $(
+                        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                        if is_x86_feature_detected!("avx2") {
+                            return |arr| unsafe { $avx2(arr) };
+                        }
+                    )?
+
+                    normal::qsort
+                });
+
+                sort(arr)
+            }
+
+            fn dispatch_partial_sort(arr: &mut [Self], k: usize) {
+                static PARTIAL_SORT: OnceLock<fn(&mut [$t], usize)> = OnceLock::new();
+
+                let partial_sort = PARTIAL_SORT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr, k| unsafe { avx::partial_sort(arr, k) };
+                    }
+
+                    normal::partial_sort
+                });
+
+                partial_sort(arr, k)
+            }
+
+            fn dispatch_select_nth_unstable(arr: &mut [Self], k: usize) {
+                static SELECT: OnceLock<fn(&mut [$t], usize)> = OnceLock::new();
+
+                let select = SELECT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr, k| unsafe {
+                            avx::select_nth_unstable(arr, k);
+                        };
+                    }
+
+                    |arr, k| {
+                        normal::select_nth_unstable(arr, k);
+                    }
+                });
+
+                select(arr, k)
+            }
+
+            fn dispatch_par_sort(arr: &mut [Self], config: &ParallelConfig) {
+                static PAR_SORT: OnceLock<fn(&mut [$t], &ParallelConfig)> = OnceLock::new();
+
+                let par_sort = PAR_SORT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr, config| unsafe { avx::par_qsort(arr, config) };
+                    }
+
+                    normal::par_qsort
+                });
+
+                par_sort(arr, config)
+            }
+
+            fn dispatch_is_sorted(arr: &[Self]) -> bool {
+                static IS_SORTED: OnceLock<fn(&[$t]) -> bool> = OnceLock::new();
+
+                let is_sorted = IS_SORTED.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr| unsafe { avx::is_sorted(arr) };
+                    }
+
+                    normal::is_sorted
+                });
+
+                is_sorted(arr)
+            }
+
+            fn dispatch_partition_le(arr: &mut [Self], pivot: Self) -> usize {
+                static PARTITION_LE: OnceLock<fn(&mut [$t], $t) -> usize> = OnceLock::new();
+
+                let partition_le = PARTITION_LE.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr, pivot| unsafe { avx::partition_le(arr, pivot) };
+                    }
+
+                    normal::partition_le
+                });
+
+                partition_le(arr, pivot)
+            }
+
+            fn dispatch_partition_lt(arr: &mut [Self], pivot: Self) -> usize {
+                static PARTITION_LT: OnceLock<fn(&mut [$t], $t) -> usize> = OnceLock::new();
+
+                let partition_lt = PARTITION_LT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr, pivot| unsafe { avx::partition_lt(arr, pivot) };
+                    }
+
+                    normal::partition_lt
+                });
+
+                partition_lt(arr, pivot)
+            }
+
+            fn dispatch_partition_between(arr: &mut [Self], low: Self, high: Self) -> usize {
+                static PARTITION_BETWEEN: OnceLock<fn(&mut [$t], $t, $t) -> usize> =
+                    OnceLock::new();
+
+                let partition_between = PARTITION_BETWEEN.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr, low, high| unsafe { avx::partition_between(arr, low, high) };
+                    }
+
+                    normal::partition_between
+                });
+
+                partition_between(arr, low, high)
+            }
+        }
+    )*};
+}
+
+impl_sort_element!(
+    i32 => avx2::qsort, u32, i64, u64, f32, f64,
+    Reverse<i32>, Reverse<u32>, Reverse<i64>, Reverse<u64>, Reverse<f32>, Reverse<f64>
+);
+
+/// 按 IEEE 754 `totalOrder` 排序的浮点数：`f32`、`f64`
+///
+/// 结果与 `sort_unstable_by(f64::total_cmp)` 相同：-0.0 在 +0.0 之前，
+/// （正的）NaN 排在最后，符号位为1的NaN排在最前。
+pub trait TotalOrder: SortElement {
+    /// 同宽度的有符号整数
+    #[doc(hidden)]
+    type Key: SortElement;
+
+    #[doc(hidden)]
+    fn as_keys(arr: &mut [Self]) -> &mut [Self::Key];
+
+    /// 与 `total_cmp` 相同的变换：符号位为1时翻转其余所有位，之后按有符号整数比较即为totalOrder。
+    /// 变换两次还原。
+    #[doc(hidden)]
+    fn flip(keys: &mut [Self::Key]);
+}
+
+macro_rules! impl_total_order {
+    ($($t:ty => $key:ty, $unsigned:ty);*) => {$(
+        impl TotalOrder for $t {
+            type Key = $key;
+
+            #[inline(always)]
+            fn as_keys(arr: &mut [Self]) -> &mut [Self::Key] {
+                unsafe { &mut *(arr as *mut [$t] as *mut [$key]) }
+            }
+
+            #[inline(always)]
+            fn flip(keys: &mut [Self::Key]) {
+                for key in keys {
+                    *key ^= (((*key >> (<$key>::BITS - 1)) as $unsigned) >> 1) as $key;
+                }
+            }
+        }
+    )*};
+}
+
+impl_total_order!(f32 => i32, u32; f64 => i64, u64);
+
+/// 运行时检测CPU特性，选择最快的实现排序（检测结果只做一次）
+pub fn sort<T: SortElement>(arr: &mut [T]) {
+    T::dispatch_sort(arr)
+}
+
+/// 降序排序，运行时选择最快的实现
+///
+/// 与对 `Reverse<T>` 排序相同：比较的方向反过来，不需要先升序排序再翻转。
+pub fn sort_desc<T>(arr: &mut [T])
+where
+    Reverse<T>: SortElement,
+{
+    sort(as_reverse(arr))
+}
+
+/// LSD基数排序，适合大量均匀分布的整数，运行时选择最快的实现
+pub fn radix_sort<T: RadixElement>(arr: &mut [T]) {
+    T::dispatch_radix_sort(arr)
+}
+
+/// 多线程排序，使用默认的 [`ParallelConfig`]，运行时选择最快的实现
+pub fn par_sort<T: SortElement>(arr: &mut [T]) {
+    par_sort_with(arr, &ParallelConfig::default())
+}
+
+/// 按`config`指定的线程数和顺序排序阈值多线程排序，运行时选择最快的实现
+pub fn par_sort_with<T: SortElement>(arr: &mut [T], config: &ParallelConfig) {
+    T::dispatch_par_sort(arr, config)
+}
+
+/// 与 `slice::select_nth_unstable` 相同，运行时选择最快的实现
+///
+/// # Panics
+/// - `k >= arr.len()`
+pub fn select_nth_unstable<T: SortElement>(
+    arr: &mut [T],
+    k: usize,
+) -> (&mut [T], &mut T, &mut [T]) {
+    T::dispatch_select_nth_unstable(arr, k);
+    split_nth(arr, k)
+}
+
+/// 把最小的`k`个元素按顺序排在`arr[..k]`，运行时选择最快的实现；`k > arr.len()` 时排序整个数组，不会panic
+pub fn partial_sort<T: SortElement>(arr: &mut [T], k: usize) {
+    T::dispatch_partial_sort(arr, k)
+}
+
+/// 与 `slice::is_sorted` 相同，运行时选择最快的实现
+pub fn is_sorted<T: SortElement>(arr: &[T]) -> bool {
+    T::dispatch_is_sorted(arr)
+}
+
+/// 把小于等于`pivot`的元素移到前面，返回它们的个数（元素原来的相对顺序不保留），运行时选择最快的实现
+pub fn partition_le<T: SortElement>(arr: &mut [T], pivot: T) -> usize {
+    T::dispatch_partition_le(arr, pivot)
+}
+
+/// 把小于`pivot`的元素移到前面，返回它们的个数（元素原来的相对顺序不保留），运行时选择最快的实现
+pub fn partition_lt<T: SortElement>(arr: &mut [T], pivot: T) -> usize {
+    T::dispatch_partition_lt(arr, pivot)
+}
+
+/// 把在 `low..=high` 之间的元素移到前面，返回它们的个数（元素原来的相对顺序不保留），
+/// 运行时选择最快的实现
+pub fn partition_between<T: SortElement>(arr: &mut [T], low: T, high: T) -> usize {
+    T::dispatch_partition_between(arr, low, high)
+}
+
+/// 按 IEEE 754 `totalOrder` 排序浮点数，运行时选择最快的实现
+pub fn sort_total<T: TotalOrder>(arr: &mut [T]) {
+    let keys = T::as_keys(arr);
+    T::flip(keys);
+    sort(keys);
+    T::flip(keys);
+}
+
+/// 按`keys`排序，`values`中的元素跟着对应的key一起移动，运行时选择最快的实现
+///
+/// # Panics
+/// - `keys.len() != values.len()`
+pub fn sort_pairs(keys: &mut [i32], values: &mut [u32]) {
+    type SortPairs = fn(&mut [i32], &mut [u32]);
+    static SORT_PAIRS: OnceLock<SortPairs> = OnceLock::new();
+
+    let sort_pairs = SORT_PAIRS.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |keys, values| unsafe { avx::sort_pairs(keys, values) };
+        }
+
+        normal::sort_pairs
+    });
+
+    sort_pairs(keys, values)
+}
+
+/// 返回使`keys`有序的下标，运行时选择最快的实现
+///
+/// # Panics
+/// - `keys.len() > u32::MAX`
+pub fn argsort(keys: &[i32]) -> Vec<u32> {
+    let len = u32::try_from(keys.len()).expect("argsort: too many keys");
+    let mut keys = keys.to_vec();
+    let mut indices: Vec<u32> = (0..len).collect();
+    sort_pairs(&mut keys, &mut indices);
+    indices
+}
+
+/// 按`key`返回的`i32`排序（不稳定），运行时选择最快的实现；每个元素只调用一次`key`
+///
+/// # Panics
+/// - `arr.len() > u32::MAX`
+pub fn sort_by_key<T, F: Fn(&T) -> i32>(arr: &mut [T], key: F) {
+    sort_by_key_with(arr, key, sort_pairs)
+}
+
+/// 把每个元素的key取到连续的数组里，用`sort_pairs`连同下标一起排序，再按下标原地重排`arr`
+fn sort_by_key_with<T, F: Fn(&T) -> i32>(
+    arr: &mut [T],
+    key: F,
+    sort_pairs: impl FnOnce(&mut [i32], &mut [u32]),
+) {
+    let len = u32::try_from(arr.len()).expect("sort_by_key: too many elements");
+    let mut keys: Vec<i32> = arr.iter().map(key).collect();
+    let mut indices: Vec<u32> = (0..len).collect();
+    sort_pairs(&mut keys, &mut indices);
+    apply_permutation(arr, &mut indices);
+}
+
+/// 原地重排，之后 `arr[i]` 是原来的 `arr[indices[i]]`。
+///
+/// 沿着置换的每个环依次交换，放好的位置在`indices`中记为自身，环回到起点时结束。
+fn apply_permutation<T>(arr: &mut [T], indices: &mut [u32]) {
+    for start in 0..arr.len() {
+        let mut cur = start;
+        loop {
+            let next = indices[cur] as usize;
+            indices[cur] = cur as u32;
+            if next == start {
+                break;
+            }
+            arr.swap(cur, next);
+            cur = next;
+        }
+    }
+}
+
+/// `Reverse` 是 `#[repr(transparent)]` 的，可以直接转换
+#[inline(always)]
+fn as_reverse<T>(arr: &mut [T]) -> &mut [Reverse<T>] {
+    unsafe { &mut *(arr as *mut [T] as *mut [Reverse<T>]) }
+}
+
+/// 快速排序递归深度的上限，超过后改用堆排序，保证最坏 O(n log n)
+#[inline]
+fn depth_limit(len: usize) -> u32 {
+    2 * (usize::BITS - len.leading_zeros())
+}
+
+/// 堆排序，`swap`交换`v`中的两个元素（可以同时交换其他数组中对应的元素）
+fn heapsort<T, F>(v: &mut [T], mut swap: F)
+where
+    T: Copy + PartialOrd,
+    F: FnMut(&mut [T], usize, usize),
+{
+    fn sift_down<T: Copy + PartialOrd>(
+        v: &mut [T],
+        swap: &mut impl FnMut(&mut [T], usize, usize),
+        mut node: usize,
+        end: usize,
+    ) {
+        loop {
+            let mut child = 2 * node + 1;
+            if child >= end {
+                break;
+            }
+            if child + 1 < end && v[child] < v[child + 1] {
+                child += 1;
+            }
+            if v[node] >= v[child] {
+                break;
+            }
+            swap(v, node, child);
+            node = child;
+        }
+    }
+
+    let len = v.len();
+    for node in (0..len / 2).rev() {
+        sift_down(v, &mut swap, node, len);
+    }
+    for end in (1..len).rev() {
+        swap(v, 0, end);
+        sift_down(v, &mut swap, 0, end);
+    }
+}
+
+/// 按 `slice::select_nth_unstable` 的形式把`v`分成 `(v[..k], v[k], v[k + 1..])`
+#[inline]
+fn split_nth<T>(v: &mut [T], k: usize) -> (&mut [T], &mut T, &mut [T]) {
+    let (left, rest) = v.split_at_mut(k);
+    let (nth, right) = rest.split_first_mut().unwrap();
+    (left, nth, right)
+}
+
+/// `slice::swap_unchecked` 还没稳定，只在开启 `nightly` feature 时使用
+///
+/// # Safety
+/// - a < v.len() && b < v.len()
+#[inline(always)]
+unsafe fn swap_unchecked<T>(v: &mut [T], a: usize, b: usize) {
+    #[cfg(feature = "nightly")]
+    unsafe {
+        v.swap_unchecked(a, b)
+    }
+
+    #[cfg(not(feature = "nightly"))]
+    unsafe {
+        let ptr = v.as_mut_ptr();
+        std::ptr::swap(ptr.add(a), ptr.add(b))
+    }
+}
@@ -1,27 +1,167 @@
-pub fn qsort(v: &mut [i32]) {
-    fn sort(v: &mut [i32], mut low: usize, mut high: usize) {
-        while low < high {
-            let (lt, gt) = partition3(v, low, high);
-            if lt - low < high - gt {
-                sort(v, low, lt);
-                low = lt + 1;
+use std::mem;
+
+use super::{
+    as_reverse, depth_limit, heapsort,
+    parallel::{par_sort, ParallelConfig},
+    sort_by_key_with, split_nth, swap_unchecked, TotalOrder,
+};
+
+pub fn qsort<T: Copy + PartialOrd>(v: &mut [T]) {
+    fn sort<T: Copy + PartialOrd>(mut v: &mut [T], mut limit: u32) {
+        while v.len() > 1 {
+            if limit == 0 {
+                heapsort(v, |v, a, b| unsafe { swap_unchecked(v, a, b) });
+                return;
+            }
+            limit -= 1;
+
+            let (lt, gt) = partition(v);
+            let (left, rest) = mem::take(&mut v).split_at_mut(lt);
+            let right = &mut rest[gt - lt..];
+
+            // 递归较短的一边，较长的一边继续循环，栈深度不超过 O(log n)
+            if left.len() < right.len() {
+                sort(left, limit);
+                v = right;
             } else {
-                sort(v, gt, high);
-                high = gt - 1;
+                sort(right, limit);
+                v = left;
             }
         }
     }
 
-    let len = v.len();
+    sort(v, depth_limit(v.len()));
+}
+
+/// 降序排序：按 `Reverse<T>` 的顺序排序，比较的方向反过来
+pub fn qsort_desc<T: Copy + PartialOrd>(v: &mut [T]) {
+    qsort(as_reverse(v));
+}
+
+/// 按 IEEE 754 `totalOrder` 排序浮点数，结果与 `sort_unstable_by(f64::total_cmp)` 相同
+pub fn qsort_total<T: TotalOrder>(v: &mut [T]) {
+    let keys = T::as_keys(v);
+    T::flip(keys);
+    qsort(keys);
+    T::flip(keys);
+}
+
+/// 重排`v`使第`k`小的元素在下标`k`处，左边的都不大于它，右边的都不小于它。
+/// 返回值与 `slice::select_nth_unstable` 相同。
+///
+/// # Panics
+/// - `k >= v.len()`
+pub fn select_nth_unstable<T: Copy + PartialOrd>(
+    v: &mut [T],
+    k: usize,
+) -> (&mut [T], &mut T, &mut [T]) {
+    select(v, k);
+    split_nth(v, k)
+}
 
-    if len <= 1 {
+/// 把最小的`k`个元素按顺序排在`v[..k]`，其余元素的顺序不确定；`k > v.len()` 时排序整个数组，不会panic
+pub fn partial_sort<T: Copy + PartialOrd>(v: &mut [T], k: usize) {
+    let k = k.min(v.len());
+    if k == 0 {
         return;
     }
+    if k < v.len() {
+        select(v, k - 1);
+        qsort(&mut v[..k - 1]);
+    } else {
+        qsort(v);
+    }
+}
+
+/// 用`partition3`划分，只在含有第`k`个元素的一边继续
+fn select<T: Copy + PartialOrd>(mut v: &mut [T], mut k: usize) {
+    assert!(
+        k < v.len(),
+        "select index {k} out of range for slice of length {}",
+        v.len()
+    );
+
+    let mut limit = depth_limit(v.len());
+    while v.len() > 1 {
+        if limit == 0 {
+            heapsort(v, |v, a, b| unsafe { swap_unchecked(v, a, b) });
+            return;
+        }
+        limit -= 1;
+
+        let (lt, gt) = partition(v);
+        if k < lt {
+            v = &mut mem::take(&mut v)[..lt];
+        } else if k >= gt {
+            v = &mut mem::take(&mut v)[gt..];
+            k -= gt;
+        } else {
+            return;
+        }
+    }
+}
+
+/// 多线程排序，每个线程里用 [`qsort`]
+pub fn par_qsort<T: Copy + PartialOrd + Send>(v: &mut [T], config: &ParallelConfig) {
+    par_sort(v, config, qsort, |v, _| partition(v));
+}
+
+/// 与 `slice::is_sorted` 相同：相邻的元素都满足 `a <= b`，有NaN时返回false
+pub fn is_sorted<T: PartialOrd>(v: &[T]) -> bool {
+    v.windows(2).all(|w| w[0] <= w[1])
+}
+
+/// 把小于等于`pivot`的元素移到前面，返回它们的个数；元素原来的相对顺序不保留
+pub fn partition_le<T: Copy + PartialOrd>(v: &mut [T], pivot: T) -> usize {
+    partition_by(v, |x| *x <= pivot)
+}
+
+/// 把小于`pivot`的元素移到前面，返回它们的个数；元素原来的相对顺序不保留
+pub fn partition_lt<T: Copy + PartialOrd>(v: &mut [T], pivot: T) -> usize {
+    partition_by(v, |x| *x < pivot)
+}
+
+/// 把在 `low..=high` 之间的元素移到前面，返回它们的个数；元素原来的相对顺序不保留
+pub fn partition_between<T: Copy + PartialOrd>(v: &mut [T], low: T, high: T) -> usize {
+    partition_by(v, |x| (low..=high).contains(x))
+}
+
+/// 把满足`pred`的元素移到前面，返回它们的个数；元素原来的相对顺序不保留
+pub fn partition_by<T>(v: &mut [T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let mut i = 0;
+    for j in 0..v.len() {
+        if pred(&v[j]) {
+            v.swap(i, j);
+            i += 1;
+        }
+    }
+    i
+}
+
+/// 三者取中后用`partition3`划分，返回 `(lt, gt)`：
+/// `v[..lt]` 小于枢轴，`v[lt..gt]` 等于枢轴，`v[gt..]` 大于枢轴
+fn partition<T: Copy + PartialOrd>(v: &mut [T]) -> (usize, usize) {
+    let high = v.len() - 1;
+    v.swap(median_of_three(v), high);
+    let (lt, gt) = partition3(v, 0, high);
+    (lt, gt + 1)
+}
 
-    sort(v, 0, len - 1);
+/// 在 1/4、1/2、3/4 处三者取中，返回下标
+fn median_of_three<T: Copy + PartialOrd>(v: &[T]) -> usize {
+    let len = v.len();
+    let (i, j, k) = (len / 4, len / 2, len / 4 * 3);
+    let (a, b, c) = (v[i], v[j], v[k]);
+    if (a <= b) == (b <= c) {
+        j
+    } else if (b <= a) == (a <= c) {
+        i
+    } else {
+        k
+    }
 }
 
-fn partition3(v: &mut [i32], low: usize, high: usize) -> (usize, usize) {
+fn partition3<T: Copy + PartialOrd>(v: &mut [T], low: usize, high: usize) -> (usize, usize) {
     let pivot = unsafe { *v.get_unchecked(high) };
     let mut i = low; // lt
     let mut j = low; // eq
@@ -31,13 +171,107 @@ fn partition3(v: &mut [i32], low: usize, high: usize) -> (usize, usize) {
         let e = unsafe { *v.get_unchecked(j) };
         if e < pivot {
             unsafe {
-                v.swap_unchecked(i, j);
+                swap_unchecked(v, i, j);
+            }
+            i += 1;
+            j += 1;
+        } else if e > pivot {
+            unsafe {
+                swap_unchecked(v, k, j);
+            }
+            k -= 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    (i, k)
+}
+
+/// 按`keys`排序，`values`中的元素跟着对应的key一起移动
+///
+/// # Panics
+/// - `keys.len() != values.len()`
+pub fn sort_pairs(keys: &mut [i32], values: &mut [u32]) {
+    fn sort(mut keys: &mut [i32], mut values: &mut [u32], mut limit: u32) {
+        while keys.len() > 1 {
+            if limit == 0 {
+                heapsort(keys, |keys, a, b| {
+                    keys.swap(a, b);
+                    values.swap(a, b);
+                });
+                return;
+            }
+            limit -= 1;
+
+            let high = keys.len() - 1;
+            let pivot = median_of_three(keys);
+            keys.swap(pivot, high);
+            values.swap(pivot, high);
+            let (lt, gt) = partition3_pairs(keys, values, 0, high);
+            let (left_keys, rest_keys) = mem::take(&mut keys).split_at_mut(lt);
+            let right_keys = &mut rest_keys[gt + 1 - lt..];
+            let (left_values, rest_values) = mem::take(&mut values).split_at_mut(lt);
+            let right_values = &mut rest_values[gt + 1 - lt..];
+
+            if left_keys.len() < right_keys.len() {
+                sort(left_keys, left_values, limit);
+                (keys, values) = (right_keys, right_values);
+            } else {
+                sort(right_keys, right_values, limit);
+                (keys, values) = (left_keys, left_values);
+            }
+        }
+    }
+
+    assert_eq!(keys.len(), values.len());
+    sort(keys, values, depth_limit(keys.len()));
+}
+
+/// 返回使`keys`有序的下标
+///
+/// # Panics
+/// - `keys.len() > u32::MAX`
+pub fn argsort(keys: &[i32]) -> Vec<u32> {
+    let len = u32::try_from(keys.len()).expect("argsort: too many keys");
+    let mut keys = keys.to_vec();
+    let mut indices: Vec<u32> = (0..len).collect();
+    sort_pairs(&mut keys, &mut indices);
+    indices
+}
+
+/// 按`key`返回的`i32`排序（不稳定）：取出key后连同下标一起排序，再原地重排`v`
+///
+/// # Panics
+/// - `v.len() > u32::MAX`
+pub fn sort_by_key<T, F: Fn(&T) -> i32>(v: &mut [T], key: F) {
+    sort_by_key_with(v, key, sort_pairs)
+}
+
+fn partition3_pairs(
+    keys: &mut [i32],
+    values: &mut [u32],
+    low: usize,
+    high: usize,
+) -> (usize, usize) {
+    let pivot = unsafe { *keys.get_unchecked(high) };
+    let mut i = low; // lt
+    let mut j = low; // eq
+    let mut k = high; // gt
+
+    while j <= k {
+        let e = unsafe { *keys.get_unchecked(j) };
+        if e < pivot {
+            unsafe {
+                swap_unchecked(keys, i, j);
+                swap_unchecked(values, i, j);
             }
             i += 1;
             j += 1;
         } else if e > pivot {
             unsafe {
-                v.swap_unchecked(k, j);
+                swap_unchecked(keys, k, j);
+                swap_unchecked(values, k, j);
             }
             k -= 1;
         } else {
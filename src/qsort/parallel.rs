@@ -0,0 +1,89 @@
+use std::{num::NonZeroUsize, thread};
+
+/// 划分需要的最小长度，更短的子数组总是顺序排序
+const MIN_PARTITION_LEN: usize = 256;
+
+/// 多线程排序的参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelConfig {
+    /// 线程数，包括调用者所在的线程；0 当作 1
+    pub threads: usize,
+    /// 不超过这个长度的子数组不再划分，在一个线程里顺序排序
+    pub sequential_cutoff: usize,
+}
+
+impl Default for ParallelConfig {
+    /// 线程数为 `thread::available_parallelism`，顺序排序的阈值为 64K 个元素
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            sequential_cutoff: 1 << 16,
+        }
+    }
+}
+
+/// 在调用者线程里划分，左右两边按长度分配剩下的线程，线程只剩一个或者数组足够短时用`sort`顺序排序。
+///
+/// `partition`划分数组并返回等于枢轴的范围 `(lt, gt)`，第二个参数是上层的枢轴（子数组的元素都不大于它）。
+pub(crate) fn par_sort<T: Copy + Send>(
+    arr: &mut [T],
+    config: &ParallelConfig,
+    sort: fn(&mut [T]),
+    partition: fn(&mut [T], Option<T>) -> (usize, usize),
+) {
+    let threads = config.threads.max(1);
+    let cutoff = config.sequential_cutoff.max(MIN_PARTITION_LEN);
+
+    if threads == 1 || arr.len() <= cutoff {
+        sort(arr);
+        return;
+    }
+
+    thread::scope(|scope| {
+        par_sort_impl(scope, arr, threads, cutoff, None, sort, partition);
+    });
+}
+
+fn par_sort_impl<'scope, T: Copy + Send>(
+    scope: &'scope thread::Scope<'scope, '_>,
+    mut arr: &'scope mut [T],
+    mut threads: usize,
+    cutoff: usize,
+    mut pred: Option<T>,
+    sort: fn(&mut [T]),
+    partition: fn(&mut [T], Option<T>) -> (usize, usize),
+) {
+    // 每次划分后较短的一边交给新线程，较长的一边留在当前线程继续划分
+    while threads > 1 && arr.len() > cutoff {
+        let (lt, gt) = partition(arr, pred);
+        let pivot = arr[lt];
+        let (left, right) = arr.split_at_mut(lt);
+        let right = &mut right[gt - lt..];
+
+        let total = left.len() + right.len();
+        let (short, short_pred, long, long_pred) = if left.len() < right.len() {
+            (left, Some(pivot), right, pred)
+        } else {
+            (right, pred, left, Some(pivot))
+        };
+
+        let short_threads = (threads * short.len() / total.max(1)).clamp(1, threads - 1);
+        scope.spawn(move || {
+            par_sort_impl(
+                scope,
+                short,
+                short_threads,
+                cutoff,
+                short_pred,
+                sort,
+                partition,
+            )
+        });
+
+        threads -= short_threads;
+        arr = long;
+        pred = long_pred;
+    }
+
+    sort(arr);
+}
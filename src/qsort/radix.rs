@@ -0,0 +1,235 @@
+#[cfg(all(feature = "avx512", target_arch = "x86_64"))]
+use std::arch::x86_64::{
+    __m512i, _mm512_add_epi32, _mm512_and_si512, _mm512_castsi256_si512, _mm512_cvtepi64_epi32,
+    _mm512_i32gather_epi32, _mm512_i32scatter_epi32, _mm512_inserti64x4, _mm512_loadu_si512,
+    _mm512_set1_epi32, _mm512_set1_epi64, _mm512_setr_epi32, _mm512_srlv_epi32, _mm512_srlv_epi64,
+    _mm512_xor_si512,
+};
+
+#[cfg(all(feature = "avx512", target_arch = "x86"))]
+use std::arch::x86::{
+    __m512i, _mm512_add_epi32, _mm512_and_si512, _mm512_castsi256_si512, _mm512_cvtepi64_epi32,
+    _mm512_i32gather_epi32, _mm512_i32scatter_epi32, _mm512_inserti64x4, _mm512_loadu_si512,
+    _mm512_set1_epi32, _mm512_set1_epi64, _mm512_setr_epi32, _mm512_srlv_epi32, _mm512_srlv_epi64,
+    _mm512_xor_si512,
+};
+
+use std::sync::OnceLock;
+
+use super::SortElement;
+
+/// 每一趟按8位分桶
+const RADIX: usize = 256;
+
+/// 可以基数排序的整数：`i32`、`u32`、`i64`、`u64`
+pub trait RadixElement: SortElement {
+    /// 字节数，即最多要分几趟
+    #[doc(hidden)]
+    const BYTES: usize;
+
+    /// 有符号数翻转符号位，之后按无符号数比较与原来的顺序相同
+    #[doc(hidden)]
+    fn to_bits(self) -> u64;
+
+    /// 读入16个元素（翻转符号位后）右移`shift`位的最低字节，每个分量32位
+    ///
+    /// # Safety
+    /// - CPU需要支持 `avx512f`
+    /// - `ptr` 开始至少有16个元素
+    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+    #[doc(hidden)]
+    unsafe fn digits(ptr: *const Self, shift: u32) -> __m512i;
+
+    #[doc(hidden)]
+    fn dispatch_radix_sort(arr: &mut [Self]);
+}
+
+macro_rules! impl_radix_element {
+    ($($t:ty: $bytes:expr, $sign:expr, |$ptr:ident, $shift:ident| $digits:block);*) => {$(
+        impl RadixElement for $t {
+            const BYTES: usize = $bytes;
+
+            #[inline(always)]
+            fn to_bits(self) -> u64 {
+                (self ^ $sign) as u64 & (u64::MAX >> (64 - 8 * $bytes))
+            }
+
+            #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+            #[inline]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn digits($ptr: *const Self, $shift: u32) -> __m512i {
+                unsafe { $digits }
+            }
+
+            fn dispatch_radix_sort(arr: &mut [Self]) {
+                static RADIX_SORT: OnceLock<fn(&mut [$t])> = OnceLock::new();
+
+                let radix_sort = RADIX_SORT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr| unsafe { radix_sort_avx(arr) };
+                    }
+
+                    radix_sort
+                });
+
+                radix_sort(arr)
+            }
+        }
+    )*};
+}
+
+impl_radix_element! {
+    i32: 4, i32::MIN, |ptr, shift| {
+        let keys = _mm512_xor_si512(_mm512_loadu_si512(ptr.cast()), _mm512_set1_epi32(i32::MIN));
+        digits32(keys, shift)
+    };
+    u32: 4, 0, |ptr, shift| {
+        digits32(_mm512_loadu_si512(ptr.cast()), shift)
+    };
+    i64: 8, i64::MIN, |ptr, shift| {
+        let sign = _mm512_set1_epi64(i64::MIN);
+        let low = _mm512_xor_si512(_mm512_loadu_si512(ptr.cast()), sign);
+        let high = _mm512_xor_si512(_mm512_loadu_si512(ptr.add(8).cast()), sign);
+        digits64(low, high, shift)
+    };
+    u64: 8, 0, |ptr, shift| {
+        digits64(_mm512_loadu_si512(ptr.cast()), _mm512_loadu_si512(ptr.add(8).cast()), shift)
+    }
+}
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn digits32(keys: __m512i, shift: u32) -> __m512i {
+    let digits = _mm512_srlv_epi32(keys, _mm512_set1_epi32(shift as i32));
+    _mm512_and_si512(digits, _mm512_set1_epi32(0xFF))
+}
+
+/// 两个向量共16个64位元素的字节，压缩成16个32位分量
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn digits64(low: __m512i, high: __m512i, shift: u32) -> __m512i {
+    let shift = _mm512_set1_epi64(shift as i64);
+    let mask = _mm512_set1_epi64(0xFF);
+    let low = _mm512_cvtepi64_epi32(_mm512_and_si512(_mm512_srlv_epi64(low, shift), mask));
+    let high = _mm512_cvtepi64_epi32(_mm512_and_si512(_mm512_srlv_epi64(high, shift), mask));
+    _mm512_inserti64x4::<1>(_mm512_castsi256_si512(low), high)
+}
+
+/// LSD基数排序：每趟按一个字节稳定地分桶，从最低字节开始；所有元素这个字节都相同的趟跳过。
+///
+/// 需要与`arr`等长的缓冲区。
+pub fn radix_sort<T: RadixElement>(arr: &mut [T]) {
+    if arr.len() <= 1 {
+        return;
+    }
+    let counts = histograms(arr);
+    scatter_passes(arr, &counts);
+}
+
+/// 与 [`radix_sort`] 相同，直方图用AVX-512统计：
+/// 16个分量各用一份直方图，gather、加一、scatter时不会有冲突，最后再把16份加起来
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+#[target_feature(enable = "avx512f")]
+pub fn radix_sort_avx<T: RadixElement>(arr: &mut [T]) {
+    if arr.len() <= 1 {
+        return;
+    }
+    let counts = unsafe { histograms_avx(arr) };
+    scatter_passes(arr, &counts);
+}
+
+/// 每个字节的直方图
+fn histograms<T: RadixElement>(arr: &[T]) -> Vec<[usize; RADIX]> {
+    let mut counts = vec![[0; RADIX]; T::BYTES];
+    for &x in arr {
+        let bits = x.to_bits();
+        for (byte, count) in counts.iter_mut().enumerate() {
+            count[(bits >> (8 * byte)) as u8 as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn histograms_avx<T: RadixElement>(arr: &[T]) -> Vec<[usize; RADIX]> {
+    const S: usize = 16;
+
+    // [字节][分量][桶]
+    let mut lanes = vec![0u32; T::BYTES * S * RADIX];
+    let lane_offsets = _mm512_setr_epi32(
+        0, 256, 512, 768, 1024, 1280, 1536, 1792, 2048, 2304, 2560, 2816, 3072, 3328, 3584, 3840,
+    );
+    let one = _mm512_set1_epi32(1);
+
+    let chunks = arr.chunks_exact(S);
+    let rest = chunks.remainder();
+    for chunk in chunks {
+        for byte in 0..T::BYTES {
+            unsafe {
+                let base = lanes.as_mut_ptr().add(byte * S * RADIX).cast::<i32>();
+                let idx =
+                    _mm512_add_epi32(T::digits(chunk.as_ptr(), 8 * byte as u32), lane_offsets);
+                let count = _mm512_i32gather_epi32::<4>(idx, base);
+                _mm512_i32scatter_epi32::<4>(base, idx, _mm512_add_epi32(count, one));
+            }
+        }
+    }
+
+    let mut counts = histograms(rest);
+    for (byte, count) in counts.iter_mut().enumerate() {
+        for lane in lanes[byte * S * RADIX..].chunks_exact(RADIX).take(S) {
+            for (c, &l) in count.iter_mut().zip(lane) {
+                *c += l as usize;
+            }
+        }
+    }
+    counts
+}
+
+/// 按`counts`逐字节分桶，在`arr`和缓冲区之间来回倒，结果放回`arr`
+fn scatter_passes<T: RadixElement>(arr: &mut [T], counts: &[[usize; RADIX]]) {
+    let len = arr.len();
+    let first = arr[0].to_bits();
+    let mut buf = arr.to_vec();
+    let mut in_arr = true;
+
+    for (byte, count) in counts.iter().enumerate() {
+        let shift = 8 * byte as u32;
+        if count[(first >> shift) as u8 as usize] == len {
+            continue;
+        }
+
+        let mut offsets = [0; RADIX];
+        let mut sum = 0;
+        for (offset, &c) in offsets.iter_mut().zip(count) {
+            *offset = sum;
+            sum += c;
+        }
+
+        let (src, dst): (&[T], &mut [T]) = if in_arr {
+            (&*arr, &mut buf)
+        } else {
+            (&buf, &mut *arr)
+        };
+        for &x in src {
+            let digit = (x.to_bits() >> shift) as u8 as usize;
+            dst[offsets[digit]] = x;
+            offsets[digit] += 1;
+        }
+        in_arr = !in_arr;
+    }
+
+    if !in_arr {
+        arr.copy_from_slice(&buf);
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m512i, _mm512_add_epi32, _mm512_cmp_epi32_mask, _mm512_i32gather_epi32, _mm512_loadu_epi32,
+    _mm512_mask_add_epi32, _mm512_mask_loadu_epi32, _mm512_set1_epi32, _mm512_setzero_si512,
+    _mm512_storeu_epi32, _MM_CMPINT_LE, _MM_CMPINT_LT,
+};
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m512i, _mm512_add_epi32, _mm512_cmp_epi32_mask, _mm512_i32gather_epi32, _mm512_loadu_epi32,
+    _mm512_mask_add_epi32, _mm512_mask_loadu_epi32, _mm512_set1_epi32, _mm512_setzero_si512,
+    _mm512_storeu_epi32, _MM_CMPINT_LE, _MM_CMPINT_LT,
+};
+
+use std::{hint, ops::Range};
+
+const S: usize = 16;
+
+/// 第一个不小于`key`的元素的下标，都小于`key`时返回 `arr.len()`
+///
+/// 无分支地二分到剩下不超过16个元素，再用一次向量比较数出其中小于`key`的个数。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn lower_bound(arr: &[i32], key: i32) -> usize {
+    unsafe { search::<_MM_CMPINT_LT>(arr, key) }
+}
+
+/// 第一个大于`key`的元素的下标，都不大于`key`时返回 `arr.len()`
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn upper_bound(arr: &[i32], key: i32) -> usize {
+    unsafe { search::<_MM_CMPINT_LE>(arr, key) }
+}
+
+/// 有序的`arr`中是否有`key`
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn contains(arr: &[i32], key: i32) -> bool {
+    arr.get(lower_bound(arr, key)) == Some(&key)
+}
+
+/// 等于`key`的元素的下标范围
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn equal_range(arr: &[i32], key: i32) -> Range<usize> {
+    lower_bound(arr, key)..upper_bound(arr, key)
+}
+
+/// 对`keys`中的每个key做 [`lower_bound`]，结果写到`out`
+///
+/// 每次取16个key，用gather同时做16个无分支的二分查找。
+/// `arr`超过 `i32::MAX` 个元素时逐个查找。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `keys.len() != out.len()`
+#[target_feature(enable = "avx512f")]
+pub fn lower_bound_batch(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    assert_eq!(keys.len(), out.len());
+    unsafe { search_batch::<_MM_CMPINT_LT>(arr, keys, out) }
+}
+
+/// 对`keys`中的每个key做 [`upper_bound`]，结果写到`out`
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+///
+/// # Panics
+/// - `keys.len() != out.len()`
+#[target_feature(enable = "avx512f")]
+pub fn upper_bound_batch(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    assert_eq!(keys.len(), out.len());
+    unsafe { search_batch::<_MM_CMPINT_LE>(arr, keys, out) }
+}
+
+/// `CMP`为 `_MM_CMPINT_LT` 时是lower_bound，`_MM_CMPINT_LE` 时是upper_bound：
+/// 返回`arr`开头满足 `x CMP key` 的元素个数
+///
+/// 每一步后答案都在 `base..=base + size` 中
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn search<const CMP: i32>(arr: &[i32], key: i32) -> usize {
+    let mut base = 0;
+    let mut size = arr.len();
+    while size > S {
+        let half = size / 2;
+        let x = unsafe { *arr.get_unchecked(base + half) };
+        // 用cmov代替分支，避免预测失败
+        base = hint::select_unpredictable(cmp_scalar::<CMP>(x, key), base + half, base);
+        size -= half;
+    }
+
+    unsafe {
+        let mask = ((1u32 << size) - 1) as u16;
+        let vals = _mm512_mask_loadu_epi32(_mm512_setzero_si512(), mask, arr.as_ptr().add(base));
+        let found = _mm512_cmp_epi32_mask::<CMP>(vals, _mm512_set1_epi32(key)) & mask;
+        base + found.count_ones() as usize
+    }
+}
+
+#[inline(always)]
+fn cmp_scalar<const CMP: i32>(x: i32, key: i32) -> bool {
+    if CMP == _MM_CMPINT_LT {
+        x < key
+    } else {
+        x <= key
+    }
+}
+
+/// 16个key一起查找，每一步用gather取出各自的中点
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn search_batch<const CMP: i32>(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    let len = arr.len();
+    if len == 0 || len > i32::MAX as usize {
+        for (o, &key) in out.iter_mut().zip(keys) {
+            *o = unsafe { search::<CMP>(arr, key) };
+        }
+        return;
+    }
+
+    let chunks = keys.chunks_exact(S);
+    let rest = chunks.remainder();
+    for (chunk, out) in chunks.zip(out.chunks_exact_mut(S)) {
+        unsafe {
+            let keys = _mm512_loadu_epi32(chunk.as_ptr());
+            let base = search16::<CMP>(arr, keys);
+            let mut result = [0u32; S];
+            _mm512_storeu_epi32(result.as_mut_ptr().cast(), base);
+            for (o, r) in out.iter_mut().zip(result) {
+                *o = r as usize;
+            }
+        }
+    }
+    let done = keys.len() - rest.len();
+    for (o, &key) in out[done..].iter_mut().zip(rest) {
+        *o = unsafe { search::<CMP>(arr, key) };
+    }
+}
+
+/// # Safety
+/// - 0 < arr.len() <= i32::MAX
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn search16<const CMP: i32>(arr: &[i32], keys: __m512i) -> __m512i {
+    let ptr = arr.as_ptr();
+    let mut base = _mm512_setzero_si512();
+    let mut size = arr.len();
+    unsafe {
+        while size > 1 {
+            let half = size / 2;
+            let half_vec = _mm512_set1_epi32(half as i32);
+            let mid = _mm512_add_epi32(base, half_vec);
+            let vals = _mm512_i32gather_epi32::<4>(mid, ptr);
+            let mask = _mm512_cmp_epi32_mask::<CMP>(vals, keys);
+            base = _mm512_mask_add_epi32(base, mask, base, half_vec);
+            size -= half;
+        }
+        let vals = _mm512_i32gather_epi32::<4>(base, ptr);
+        let mask = _mm512_cmp_epi32_mask::<CMP>(vals, keys);
+        _mm512_mask_add_epi32(base, mask, base, _mm512_set1_epi32(1))
+    }
+}
@@ -0,0 +1,88 @@
+use std::{ops::Range, sync::OnceLock};
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+/// 最后几层用向量比较的二分查找，以及用gather同时查找16个key
+pub mod avx;
+pub mod normal;
+
+/// 在有序的`arr`中找第一个不小于`key`的元素的下标，运行时选择最快的实现（检测结果只做一次）
+pub fn lower_bound(arr: &[i32], key: i32) -> usize {
+    static LOWER_BOUND: OnceLock<fn(&[i32], i32) -> usize> = OnceLock::new();
+
+    let lower_bound = LOWER_BOUND.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |arr, key| unsafe { avx::lower_bound(arr, key) };
+        }
+
+        normal::lower_bound
+    });
+
+    lower_bound(arr, key)
+}
+
+/// 在有序的`arr`中找第一个大于`key`的元素的下标，运行时选择最快的实现
+pub fn upper_bound(arr: &[i32], key: i32) -> usize {
+    static UPPER_BOUND: OnceLock<fn(&[i32], i32) -> usize> = OnceLock::new();
+
+    let upper_bound = UPPER_BOUND.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |arr, key| unsafe { avx::upper_bound(arr, key) };
+        }
+
+        normal::upper_bound
+    });
+
+    upper_bound(arr, key)
+}
+
+/// 有序的`arr`中是否有`key`，运行时选择最快的实现
+pub fn contains(arr: &[i32], key: i32) -> bool {
+    arr.get(lower_bound(arr, key)) == Some(&key)
+}
+
+/// 有序的`arr`中等于`key`的元素的下标范围，运行时选择最快的实现
+pub fn equal_range(arr: &[i32], key: i32) -> Range<usize> {
+    lower_bound(arr, key)..upper_bound(arr, key)
+}
+
+/// 对`keys`中的每个key做 [`lower_bound`]，结果写到`out`，运行时选择最快的实现
+///
+/// # Panics
+/// - `keys.len() != out.len()`
+pub fn lower_bound_batch(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    type SearchBatch = fn(&[i32], &[i32], &mut [usize]);
+    static LOWER_BOUND_BATCH: OnceLock<SearchBatch> = OnceLock::new();
+
+    let lower_bound_batch = LOWER_BOUND_BATCH.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |arr, keys, out| unsafe { avx::lower_bound_batch(arr, keys, out) };
+        }
+
+        normal::lower_bound_batch
+    });
+
+    lower_bound_batch(arr, keys, out)
+}
+
+/// 对`keys`中的每个key做 [`upper_bound`]，结果写到`out`，运行时选择最快的实现
+///
+/// # Panics
+/// - `keys.len() != out.len()`
+pub fn upper_bound_batch(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    type SearchBatch = fn(&[i32], &[i32], &mut [usize]);
+    static UPPER_BOUND_BATCH: OnceLock<SearchBatch> = OnceLock::new();
+
+    let upper_bound_batch = UPPER_BOUND_BATCH.get_or_init(|| {
+        #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+        if is_x86_feature_detected!("avx512f") {
+            return |arr, keys, out| unsafe { avx::upper_bound_batch(arr, keys, out) };
+        }
+
+        normal::upper_bound_batch
+    });
+
+    upper_bound_batch(arr, keys, out)
+}
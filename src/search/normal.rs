@@ -0,0 +1,43 @@
+use std::ops::Range;
+
+/// 第一个不小于`key`的元素的下标，都小于`key`时返回 `arr.len()`
+pub fn lower_bound(arr: &[i32], key: i32) -> usize {
+    arr.partition_point(|&x| x < key)
+}
+
+/// 第一个大于`key`的元素的下标，都不大于`key`时返回 `arr.len()`
+pub fn upper_bound(arr: &[i32], key: i32) -> usize {
+    arr.partition_point(|&x| x <= key)
+}
+
+/// 有序的`arr`中是否有`key`
+pub fn contains(arr: &[i32], key: i32) -> bool {
+    arr.get(lower_bound(arr, key)) == Some(&key)
+}
+
+/// 等于`key`的元素的下标范围
+pub fn equal_range(arr: &[i32], key: i32) -> Range<usize> {
+    lower_bound(arr, key)..upper_bound(arr, key)
+}
+
+/// 对`keys`中的每个key做 [`lower_bound`]，结果写到`out`
+///
+/// # Panics
+/// - `keys.len() != out.len()`
+pub fn lower_bound_batch(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    assert_eq!(keys.len(), out.len());
+    for (o, &key) in out.iter_mut().zip(keys) {
+        *o = lower_bound(arr, key);
+    }
+}
+
+/// 对`keys`中的每个key做 [`upper_bound`]，结果写到`out`
+///
+/// # Panics
+/// - `keys.len() != out.len()`
+pub fn upper_bound_batch(arr: &[i32], keys: &[i32], out: &mut [usize]) {
+    assert_eq!(keys.len(), out.len());
+    for (o, &key) in out.iter_mut().zip(keys) {
+        *o = upper_bound(arr, key);
+    }
+}
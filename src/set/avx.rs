@@ -0,0 +1,175 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m512i, _mm512_alignr_epi32, _mm512_cmpeq_epi32_mask, _mm512_cmpneq_epi32_mask,
+    _mm512_loadu_epi32, _mm512_mask_compressstoreu_epi32, _mm512_set1_epi32, _mm512_setzero_si512,
+};
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m512i, _mm512_alignr_epi32, _mm512_cmpeq_epi32_mask, _mm512_cmpneq_epi32_mask,
+    _mm512_loadu_epi32, _mm512_mask_compressstoreu_epi32, _mm512_set1_epi32, _mm512_setzero_si512,
+};
+
+use super::{normal, tail, SetElement};
+use crate::{merge::avx::merge_impl, qsort::avx::SimdElement};
+
+const S: usize = 16;
+
+/// 有序、无重复的`a`与`b`的交集
+///
+/// 两边各取16个元素，`b`的每个元素广播后与`a`的向量比较（全对比较），
+/// 相等的分量用compress-store写出；末尾较小的一边前进。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn intersect<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    // `a`的每个元素最多写出一次；输入有重复元素时交集可能比`b`长，不能按较短的一边分配
+    let mut out = Vec::<T>::with_capacity(a.len());
+    let ptr = out.as_mut_ptr().cast::<i32>();
+    let mut n = 0;
+    let (i, j, matched) = unsafe {
+        all_pairs(a, b, |va, matched| {
+            _mm512_mask_compressstoreu_epi32(ptr.add(n), matched, va);
+            n += matched.count_ones() as usize;
+        })
+    };
+    unsafe { out.set_len(n) };
+    tail(a, b, i, j, matched as u32, |x, in_b| {
+        if in_b {
+            out.push(x);
+        }
+    });
+    out
+}
+
+/// 交集的元素个数
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn intersect_count<T: SetElement>(a: &[T], b: &[T]) -> usize {
+    let mut count = 0;
+    let (i, j, matched) = unsafe {
+        all_pairs(a, b, |_, matched| {
+            count += matched.count_ones() as usize;
+        })
+    };
+    tail(a, b, i, j, matched as u32, |_, in_b| count += in_b as usize);
+    count
+}
+
+/// 在`a`中而不在`b`中的元素，`a`、`b`有序、无重复
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn difference<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = Vec::<T>::with_capacity(a.len());
+    let ptr = out.as_mut_ptr().cast::<i32>();
+    let mut n = 0;
+    let (i, j, matched) = unsafe {
+        all_pairs(a, b, |va, matched| {
+            _mm512_mask_compressstoreu_epi32(ptr.add(n), !matched, va);
+            n += S - matched.count_ones() as usize;
+        })
+    };
+    unsafe { out.set_len(n) };
+    tail(a, b, i, j, matched as u32, |x, in_b| {
+        if !in_b {
+            out.push(x);
+        }
+    });
+    out
+}
+
+/// 有序、无重复的`a`与`b`的并集：用双调合并网络合并后，再去掉相邻的重复元素
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn union<T: SetElement + SimdElement>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = vec![T::default(); a.len() + b.len()];
+    unsafe { merge_impl(a, b, &mut out) };
+    let len = dedup(&mut out);
+    out.truncate(len);
+    out
+}
+
+/// 去掉有序的`arr`中相邻的重复元素，不重复的元素按顺序移到前面，返回它们的个数
+///
+/// 每个向量与错开一个分量的自己比较，不相等的分量用compress-store写出。
+///
+/// # Safety
+/// - CPU需要支持 `avx512f`
+#[target_feature(enable = "avx512f")]
+pub fn dedup<T: SetElement>(arr: &mut [T]) -> usize {
+    let len = arr.len();
+    if len < S {
+        return normal::dedup(arr);
+    }
+
+    let ptr = arr.as_mut_ptr().cast::<i32>();
+    let mut w = 0;
+    let mut i = 0;
+    let mut prev = _mm512_setzero_si512();
+    while i + S <= len {
+        unsafe {
+            let val = _mm512_loadu_epi32(ptr.add(i));
+            // 每个分量的前一个元素：上一个向量的最后一个分量接上这个向量的前15个分量
+            let shifted = _mm512_alignr_epi32::<15>(val, prev);
+            let mut mask = _mm512_cmpneq_epi32_mask(val, shifted);
+            if i == 0 {
+                mask |= 1;
+            }
+            _mm512_mask_compressstoreu_epi32(ptr.add(w), mask, val);
+            w += mask.count_ones() as usize;
+            prev = val;
+        }
+        i += S;
+    }
+
+    // 最后保留的元素与`arr[i - 1]`相等
+    for i in i..len {
+        if arr[i] != arr[w - 1] {
+            arr[w] = arr[i];
+            w += 1;
+        }
+    }
+    w
+}
+
+/// 按块做全对比较：`a`的块与`b`的块中相等的分量记在`matched`中，
+/// `a`的块前进时调用 `emit(a的块, matched)`。
+///
+/// 返回不满一块时的 `(i, j, matched)`，`matched`是`a[i..]`的第一块已经知道在`b`中的分量
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn all_pairs<T: SetElement>(
+    a: &[T],
+    b: &[T],
+    mut emit: impl FnMut(__m512i, u16),
+) -> (usize, usize, u16) {
+    let (pa, pb) = (a.as_ptr().cast::<i32>(), b.as_ptr().cast::<i32>());
+    let (mut i, mut j) = (0, 0);
+    let mut matched = 0;
+    while i + S <= a.len() && j + S <= b.len() {
+        unsafe {
+            let va = _mm512_loadu_epi32(pa.add(i));
+            for k in 0..S {
+                matched |= _mm512_cmpeq_epi32_mask(va, _mm512_set1_epi32(*pb.add(j + k)));
+            }
+
+            let (a_last, b_last) = (*a.get_unchecked(i + S - 1), *b.get_unchecked(j + S - 1));
+            if a_last <= b_last {
+                emit(va, matched);
+                matched = 0;
+                i += S;
+            }
+            if b_last <= a_last {
+                j += S;
+            }
+        }
+    }
+    (i, j, matched)
+}
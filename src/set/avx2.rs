@@ -0,0 +1,186 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m256i, _mm256_blend_epi32, _mm256_cmpeq_epi32, _mm256_loadu_si256, _mm256_or_si256,
+    _mm256_permutevar8x32_epi32, _mm256_set1_epi32, _mm256_setr_epi32, _mm256_setzero_si256,
+    _mm256_storeu_si256,
+};
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m256i, _mm256_blend_epi32, _mm256_cmpeq_epi32, _mm256_loadu_si256, _mm256_or_si256,
+    _mm256_permutevar8x32_epi32, _mm256_set1_epi32, _mm256_setr_epi32, _mm256_setzero_si256,
+    _mm256_storeu_si256,
+};
+
+use super::{normal, tail, SetElement};
+use crate::qsort::avx2::{movemask, permute};
+
+const S: usize = 8;
+
+/// 有序、无重复的`a`与`b`的交集，与 [`avx::intersect`](super::avx::intersect) 相同，每块8个元素，
+/// 没有 `compressstore`，用查表重排后整个向量写出
+///
+/// # Safety
+/// - CPU需要支持 `avx2`
+#[target_feature(enable = "avx2")]
+pub fn intersect<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    // `a`的每个元素最多写出一次，整个向量写出时最多多写`S`个元素；
+    // 输入有重复元素时交集可能比`b`长，不能按较短的一边分配
+    let mut out = Vec::<T>::with_capacity(a.len() + S);
+    let ptr = out.as_mut_ptr();
+    let mut n = 0;
+    let (i, j, matched) = unsafe {
+        all_pairs(a, b, |va, matched| {
+            _mm256_storeu_si256(ptr.add(n).cast(), permute(va, matched));
+            n += matched.count_ones() as usize;
+        })
+    };
+    unsafe { out.set_len(n) };
+    tail(a, b, i, j, matched as u32, |x, in_b| {
+        if in_b {
+            out.push(x);
+        }
+    });
+    out
+}
+
+/// 交集的元素个数
+///
+/// # Safety
+/// - CPU需要支持 `avx2`
+#[target_feature(enable = "avx2")]
+pub fn intersect_count<T: SetElement>(a: &[T], b: &[T]) -> usize {
+    let mut count = 0;
+    let (i, j, matched) = unsafe {
+        all_pairs(a, b, |_, matched| {
+            count += matched.count_ones() as usize;
+        })
+    };
+    tail(a, b, i, j, matched as u32, |_, in_b| count += in_b as usize);
+    count
+}
+
+/// 在`a`中而不在`b`中的元素，`a`、`b`有序、无重复
+///
+/// # Safety
+/// - CPU需要支持 `avx2`
+#[target_feature(enable = "avx2")]
+pub fn difference<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = Vec::<T>::with_capacity(a.len() + S);
+    let ptr = out.as_mut_ptr();
+    let mut n = 0;
+    let (i, j, matched) = unsafe {
+        all_pairs(a, b, |va, matched| {
+            _mm256_storeu_si256(ptr.add(n).cast(), permute(va, !matched));
+            n += S - matched.count_ones() as usize;
+        })
+    };
+    unsafe { out.set_len(n) };
+    tail(a, b, i, j, matched as u32, |x, in_b| {
+        if !in_b {
+            out.push(x);
+        }
+    });
+    out
+}
+
+/// 有序、无重复的`a`与`b`的并集：先用全对比较求出不在`a`中的`b`的元素，
+/// 它与`a`没有相同的元素，再无分支地合并
+///
+/// # Safety
+/// - CPU需要支持 `avx2`
+#[target_feature(enable = "avx2")]
+pub fn union<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    let rest = difference(b, a);
+    let mut out = Vec::with_capacity(a.len() + rest.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < rest.len() {
+        let take_a = a[i] < rest[j];
+        out.push(if take_a { a[i] } else { rest[j] });
+        i += take_a as usize;
+        j += !take_a as usize;
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&rest[j..]);
+    out
+}
+
+/// 去掉有序的`arr`中相邻的重复元素，不重复的元素按顺序移到前面，返回它们的个数
+///
+/// # Safety
+/// - CPU需要支持 `avx2`
+#[target_feature(enable = "avx2")]
+pub fn dedup<T: SetElement>(arr: &mut [T]) -> usize {
+    let len = arr.len();
+    if len < S {
+        return normal::dedup(arr);
+    }
+
+    let ptr = arr.as_mut_ptr();
+    // 每个分量换到下一个位置，最后一个分量换到第0个
+    let rotate = _mm256_setr_epi32(7, 0, 1, 2, 3, 4, 5, 6);
+    let mut w = 0;
+    let mut i = 0;
+    let mut prev = _mm256_setzero_si256();
+    while i + S <= len {
+        unsafe {
+            let val = _mm256_loadu_si256(ptr.add(i).cast());
+            let shifted = _mm256_blend_epi32::<1>(
+                _mm256_permutevar8x32_epi32(val, rotate),
+                _mm256_permutevar8x32_epi32(prev, rotate),
+            );
+            let mut mask = !movemask(_mm256_cmpeq_epi32(val, shifted));
+            if i == 0 {
+                mask |= 1;
+            }
+            // 写出的位置不超过`i + S`，不会覆盖还没读的元素
+            _mm256_storeu_si256(ptr.add(w).cast(), permute(val, mask));
+            w += mask.count_ones() as usize;
+            prev = val;
+        }
+        i += S;
+    }
+
+    for i in i..len {
+        if arr[i] != arr[w - 1] {
+            arr[w] = arr[i];
+            w += 1;
+        }
+    }
+    w
+}
+
+/// 按块做全对比较，见 [`avx`](super::avx) 中的同名函数
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn all_pairs<T: SetElement>(
+    a: &[T],
+    b: &[T],
+    mut emit: impl FnMut(__m256i, u8),
+) -> (usize, usize, u8) {
+    let (pa, pb) = (a.as_ptr(), b.as_ptr().cast::<i32>());
+    let (mut i, mut j) = (0, 0);
+    let mut matched = 0;
+    while i + S <= a.len() && j + S <= b.len() {
+        unsafe {
+            let va = _mm256_loadu_si256(pa.add(i).cast());
+            let mut eq = _mm256_setzero_si256();
+            for k in 0..S {
+                let vb = _mm256_set1_epi32(*pb.add(j + k));
+                eq = _mm256_or_si256(eq, _mm256_cmpeq_epi32(va, vb));
+            }
+            matched |= movemask(eq);
+
+            let (a_last, b_last) = (*a.get_unchecked(i + S - 1), *b.get_unchecked(j + S - 1));
+            if a_last <= b_last {
+                emit(va, matched);
+                matched = 0;
+                i += S;
+            }
+            if b_last <= a_last {
+                j += S;
+            }
+        }
+    }
+    (i, j, matched)
+}
@@ -0,0 +1,192 @@
+use std::sync::OnceLock;
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+/// 全对比较的集合运算，结果用 `compressstore` 写出
+pub mod avx;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub mod avx2;
+pub mod normal;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+type SetOp<T> = fn(&[T], &[T]) -> Vec<T>;
+
+/// 可以做集合运算的元素类型：`i32`、`u32`
+pub trait SetElement: Copy + Ord + Default + sealed::Sealed {
+    #[doc(hidden)]
+    fn dispatch_intersect(a: &[Self], b: &[Self]) -> Vec<Self>;
+
+    #[doc(hidden)]
+    fn dispatch_intersect_count(a: &[Self], b: &[Self]) -> usize;
+
+    #[doc(hidden)]
+    fn dispatch_union(a: &[Self], b: &[Self]) -> Vec<Self>;
+
+    #[doc(hidden)]
+    fn dispatch_difference(a: &[Self], b: &[Self]) -> Vec<Self>;
+
+    #[doc(hidden)]
+    fn dispatch_dedup(arr: &mut [Self]) -> usize;
+}
+
+macro_rules! impl_set_element {
+    ($($t:ty),*) => {$(
+        impl sealed::Sealed for $t {}
+
+        impl SetElement for $t {
+            fn dispatch_intersect(a: &[Self], b: &[Self]) -> Vec<Self> {
+                static INTERSECT: OnceLock<SetOp<$t>> = OnceLock::new();
+
+                let intersect = INTERSECT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |a, b| unsafe { avx::intersect(a, b) };
+                    }
+
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    if is_x86_feature_detected!("avx2") {
+                        return |a, b| unsafe { avx2::intersect(a, b) };
+                    }
+
+                    normal::intersect
+                });
+
+                intersect(a, b)
+            }
+
+            fn dispatch_intersect_count(a: &[Self], b: &[Self]) -> usize {
+                static INTERSECT_COUNT: OnceLock<fn(&[$t], &[$t]) -> usize> = OnceLock::new();
+
+                let intersect_count = INTERSECT_COUNT.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |a, b| unsafe { avx::intersect_count(a, b) };
+                    }
+
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    if is_x86_feature_detected!("avx2") {
+                        return |a, b| unsafe { avx2::intersect_count(a, b) };
+                    }
+
+                    normal::intersect_count
+                });
+
+                intersect_count(a, b)
+            }
+
+            fn dispatch_union(a: &[Self], b: &[Self]) -> Vec<Self> {
+                static UNION: OnceLock<SetOp<$t>> = OnceLock::new();
+
+                let union = UNION.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |a, b| unsafe { avx::union(a, b) };
+                    }
+
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    if is_x86_feature_detected!("avx2") {
+                        return |a, b| unsafe { avx2::union(a, b) };
+                    }
+
+                    normal::union
+                });
+
+                union(a, b)
+            }
+
+            fn dispatch_difference(a: &[Self], b: &[Self]) -> Vec<Self> {
+                static DIFFERENCE: OnceLock<SetOp<$t>> = OnceLock::new();
+
+                let difference = DIFFERENCE.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |a, b| unsafe { avx::difference(a, b) };
+                    }
+
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    if is_x86_feature_detected!("avx2") {
+                        return |a, b| unsafe { avx2::difference(a, b) };
+                    }
+
+                    normal::difference
+                });
+
+                difference(a, b)
+            }
+
+            fn dispatch_dedup(arr: &mut [Self]) -> usize {
+                static DEDUP: OnceLock<fn(&mut [$t]) -> usize> = OnceLock::new();
+
+                let dedup = DEDUP.get_or_init(|| {
+                    #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+                    if is_x86_feature_detected!("avx512f") {
+                        return |arr| unsafe { avx::dedup(arr) };
+                    }
+
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    if is_x86_feature_detected!("avx2") {
+                        return |arr| unsafe { avx2::dedup(arr) };
+                    }
+
+                    normal::dedup
+                });
+
+                dedup(arr)
+            }
+        }
+    )*};
+}
+
+impl_set_element!(i32, u32);
+
+/// 有序、无重复的`a`与`b`的交集，运行时选择最快的实现（检测结果只做一次）
+pub fn intersect<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    T::dispatch_intersect(a, b)
+}
+
+/// 有序、无重复的`a`与`b`的交集的元素个数，运行时选择最快的实现
+pub fn intersect_count<T: SetElement>(a: &[T], b: &[T]) -> usize {
+    T::dispatch_intersect_count(a, b)
+}
+
+/// 有序、无重复的`a`与`b`的并集，运行时选择最快的实现
+pub fn union<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    T::dispatch_union(a, b)
+}
+
+/// 在`a`中而不在`b`中的元素，`a`、`b`有序、无重复，运行时选择最快的实现
+pub fn difference<T: SetElement>(a: &[T], b: &[T]) -> Vec<T> {
+    T::dispatch_difference(a, b)
+}
+
+/// 去掉有序的`arr`中相邻的重复元素，不重复的元素按顺序移到前面，返回它们的个数，
+/// 运行时选择最快的实现
+pub fn dedup<T: SetElement>(arr: &mut [T]) -> usize {
+    T::dispatch_dedup(arr)
+}
+
+/// 向量部分处理完后剩下的`a[i..]`与`b[j..]`逐个合并，对`a[i..]`的每个元素调用 `f(元素, 是否在b中)`
+///
+/// `matched`是`a[i..]`开头已经在`b[..j]`之后的块中找到的元素，这些元素不再与`b`比较
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn tail<T: Ord + Copy>(
+    a: &[T],
+    b: &[T],
+    i: usize,
+    mut j: usize,
+    matched: u32,
+    mut f: impl FnMut(T, bool),
+) {
+    for (k, &x) in a[i..].iter().enumerate() {
+        if k < 32 && matched >> k & 1 == 1 {
+            f(x, true);
+            continue;
+        }
+        while j < b.len() && b[j] < x {
+            j += 1;
+        }
+        f(x, j < b.len() && b[j] == x);
+    }
+}
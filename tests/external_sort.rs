@@ -0,0 +1,156 @@
+//! `qsort::external_sort` 与 `sort_unstable` 的差分测试
+
+use std::{
+    env,
+    fmt::Debug,
+    fs,
+    io::ErrorKind,
+    mem,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rand::{distributions::Standard, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+use simd_demo::qsort::{external_sort, ExternalSortConfig, Record};
+
+/// 内存预算的下限是 8 KiB，这时 `i32` 每个顺串 2048 个元素，每一趟合并2路
+const MIN_CHUNK_I32: usize = 2048;
+
+/// 按本机字节序和文件互相转换
+trait Bytes: Record + Debug {
+    fn to_bytes(arr: &[Self]) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8]) -> Vec<Self>;
+}
+
+macro_rules! impl_bytes {
+    ($($t:ty),*) => {
+        $(
+        impl Bytes for $t {
+            fn to_bytes(arr: &[Self]) -> Vec<u8> {
+                arr.iter().flat_map(|x| x.to_ne_bytes()).collect()
+            }
+
+            fn from_bytes(bytes: &[u8]) -> Vec<Self> {
+                bytes
+                    .chunks_exact(mem::size_of::<$t>())
+                    .map(|b| <$t>::from_ne_bytes(b.try_into().unwrap()))
+                    .collect()
+            }
+        }
+        )*
+    };
+}
+
+impl_bytes!(i32, u32, i64, u64);
+
+/// 每个测试自己的临时目录，drop时删除
+struct TmpDir(PathBuf);
+
+impl TmpDir {
+    fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("simd_demo-external-{}-{id}", process::id()));
+        fs::create_dir_all(path.join("runs")).unwrap();
+        Self(path)
+    }
+
+    fn config(&self, memory: usize) -> ExternalSortConfig {
+        ExternalSortConfig {
+            memory,
+            tmp_dir: self.0.join("runs"),
+        }
+    }
+
+    /// 临时顺串文件都已经删除
+    fn assert_no_runs(&self) {
+        let left: Vec<_> = fs::read_dir(self.0.join("runs")).unwrap().collect();
+        assert!(left.is_empty(), "left {left:?}");
+    }
+}
+
+impl Drop for TmpDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 排序写到文件里的`arr`，结果与 `sort_unstable` 相同
+fn check<T: Bytes + Ord>(arr: &[T], memory: usize) {
+    let dir = TmpDir::new();
+    let (input, output) = (dir.0.join("input"), dir.0.join("output"));
+    fs::write(&input, T::to_bytes(arr)).unwrap();
+    external_sort::<T>(&input, &output, &dir.config(memory)).unwrap();
+
+    let mut expected = arr.to_vec();
+    expected.sort_unstable();
+    let actual = T::from_bytes(&fs::read(&output).unwrap());
+    assert_eq!(actual, expected, "len {} memory {memory}", arr.len());
+    dir.assert_no_runs();
+}
+
+fn random<T>(rng: &mut StdRng, len: usize) -> Vec<T>
+where
+    Standard: Distribution<T>,
+{
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn empty() {
+    check::<i32>(&[], 0);
+    check::<u64>(&[], 1 << 20);
+}
+
+#[test]
+fn single_chunk() {
+    let mut rng = StdRng::seed_from_u64(0xe1);
+    // 不满一个顺串时直接写到输出，正好一个顺串时经过一次合并
+    for len in [1, 2, 100, MIN_CHUNK_I32 - 1, MIN_CHUNK_I32] {
+        check(&random::<i32>(&mut rng, len), 0);
+    }
+    check(&random::<u64>(&mut rng, 1000), 1 << 20);
+}
+
+#[test]
+fn many_runs() {
+    let mut rng = StdRng::seed_from_u64(0xe2);
+    // 每一趟合并2路，十几个顺串要合并好几趟
+    for len in [2 * MIN_CHUNK_I32 + 1, 13 * MIN_CHUNK_I32 + 123] {
+        check(&random::<i32>(&mut rng, len), 0);
+        let dups: Vec<i32> = (0..len).map(|_| rng.gen_range(-3..3)).collect();
+        check(&dups, 0);
+    }
+    check(&random::<u32>(&mut rng, 5 * MIN_CHUNK_I32 + 7), 0);
+    check(&random::<i64>(&mut rng, 5 * MIN_CHUNK_I32 / 2 + 7), 0);
+    // 内存较大时一趟合并所有顺串
+    check(&random::<u64>(&mut rng, 100_000), 64 << 10);
+}
+
+#[test]
+fn exact_multiple_of_chunk() {
+    let mut rng = StdRng::seed_from_u64(0xe3);
+    for runs in [2, 3, 8] {
+        check(&random::<i32>(&mut rng, runs * MIN_CHUNK_I32), 0);
+        check(&random::<u64>(&mut rng, runs * MIN_CHUNK_I32 / 2), 0);
+    }
+}
+
+#[test]
+fn truncated_record() {
+    let mut rng = StdRng::seed_from_u64(0xe4);
+    // 最后一条记录不完整，出错在第一个顺串或者写了几个顺串之后
+    for len in [0, 10, 3 * MIN_CHUNK_I32 + 10] {
+        let dir = TmpDir::new();
+        let (input, output) = (dir.0.join("input"), dir.0.join("output"));
+        let mut bytes = i32::to_bytes(&random(&mut rng, len));
+        bytes.extend([1, 2, 3]);
+        fs::write(&input, bytes).unwrap();
+
+        let err = external_sort::<i32>(&input, &output, &dir.config(0)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "len {len}");
+        dir.assert_no_runs();
+    }
+}
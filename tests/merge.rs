@@ -0,0 +1,159 @@
+//! `merge` 的各实现与标量合并、`sort_unstable` 的差分测试
+//!
+//! `merge::avx` 中合并其他元素类型的部分由 `set::avx::union` 使用，在 `tests/set.rs` 中测试。
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "avx512")]
+use simd_demo::merge::avx;
+use simd_demo::merge::{self, normal};
+
+type Merge = fn(&[i32], &[i32], &mut [i32]);
+
+/// 一种实现的 `merge` 与 `mergesort`
+struct Backend {
+    name: &'static str,
+    merge: Merge,
+    mergesort: fn(&mut [i32]),
+}
+
+fn backends() -> Vec<Backend> {
+    #[allow(unused_mut)]
+    let mut backends = vec![
+        Backend {
+            name: "normal",
+            merge: normal::merge,
+            mergesort: normal::mergesort,
+        },
+        Backend {
+            name: "dispatch",
+            merge: merge::merge,
+            mergesort: merge::mergesort,
+        },
+    ];
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512f") {
+        backends.push(Backend {
+            name: "avx",
+            merge: |a, b, out| unsafe { avx::merge(a, b, out) },
+            mergesort: |arr| unsafe { avx::mergesort(arr) },
+        });
+    }
+    backends
+}
+
+/// 随机、大量重复、含有与填充相同的 `i32::MAX` 的数组
+fn input(rng: &mut StdRng, len: usize) -> Vec<i32> {
+    match rng.gen_range(0..4) {
+        0 => (0..len).map(|_| rng.gen()).collect(),
+        1 => (0..len).map(|_| rng.gen_range(0..4)).collect(),
+        2 => (0..len)
+            .map(|_| match rng.gen_range(0..4) {
+                0 => i32::MIN,
+                1 => i32::MAX,
+                _ => rng.gen_range(-2..=2),
+            })
+            .collect(),
+        _ => vec![i32::MAX; len],
+    }
+}
+
+fn sorted_input(rng: &mut StdRng, len: usize) -> Vec<i32> {
+    let mut arr = input(rng, len);
+    arr.sort_unstable();
+    arr
+}
+
+/// 逐个比较两边的首元素
+fn scalar_merge(a: &[i32], b: &[i32]) -> Vec<i32> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    while i < a.len() && j < b.len() {
+        if a[i] <= b[j] {
+            out.push(a[i]);
+            i += 1;
+        } else {
+            out.push(b[j]);
+            j += 1;
+        }
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+    out
+}
+
+fn check_merge(backends: &[Backend], a: &[i32], b: &[i32]) {
+    let expected = scalar_merge(a, b);
+    for backend in backends {
+        let mut out = vec![0; a.len() + b.len()];
+        (backend.merge)(a, b, &mut out);
+        assert_eq!(
+            out,
+            expected,
+            "{} len {} + {}",
+            backend.name,
+            a.len(),
+            b.len()
+        );
+    }
+}
+
+#[test]
+fn merge_lengths() {
+    let backends = backends();
+    let mut rng = StdRng::seed_from_u64(0x3e6);
+    // 两边各自跨过几个向量，包括空的一边
+    for len_a in 0..=50 {
+        for len_b in 0..=50 {
+            let a = sorted_input(&mut rng, len_a);
+            let b = sorted_input(&mut rng, len_b);
+            check_merge(&backends, &a, &b);
+        }
+    }
+    // 长度相差很大
+    for (len_a, len_b) in [(1000, 3), (5, 10_000), (4097, 4096), (0, 100_000)] {
+        let a = sorted_input(&mut rng, len_a);
+        let b = sorted_input(&mut rng, len_b);
+        check_merge(&backends, &a, &b);
+        check_merge(&backends, &b, &a);
+    }
+}
+
+#[test]
+fn merge_padding_values() {
+    let backends = backends();
+    let mut rng = StdRng::seed_from_u64(0x3e7);
+    // 一边的结尾全是 `i32::MAX`，与另一边填充的值相同
+    for len_a in [1, 15, 16, 17, 33, 100] {
+        for max_count in [1, 16, 17, 40] {
+            let mut a = sorted_input(&mut rng, len_a);
+            a.extend(std::iter::repeat_n(i32::MAX, max_count));
+            let len_b = rng.gen_range(0..50);
+            let b = sorted_input(&mut rng, len_b);
+            check_merge(&backends, &a, &b);
+            check_merge(&backends, &b, &a);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn merge_wrong_output_len() {
+    merge::merge(&[1, 2], &[3], &mut [0; 2]);
+}
+
+#[test]
+fn mergesort() {
+    let backends = backends();
+    let mut rng = StdRng::seed_from_u64(0x3e8);
+    // 跨过标量和AVX-512实现的段长（32、128）和它们的倍数
+    for len in (0..=300).chain([511, 512, 513, 1000, 4097, 100_000]) {
+        let arr = input(&mut rng, len);
+        let mut expected = arr.clone();
+        expected.sort_unstable();
+        for backend in &backends {
+            let mut actual = arr.clone();
+            (backend.mergesort)(&mut actual);
+            assert_eq!(actual, expected, "{} len {len}", backend.name);
+        }
+    }
+}
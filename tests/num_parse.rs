@@ -0,0 +1,321 @@
+//! `num_parse` 的所有实现与 `str::parse` 的差分测试
+
+use std::{fmt::Debug, num, str::FromStr};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+#[cfg(feature = "avx512")]
+use simd_demo::num_parse::avx;
+use simd_demo::num_parse::{self, normal, IntErrorKind, ParseIntError};
+
+type Parse<T> = fn(&str) -> Option<T>;
+type TryParse<T> = fn(&str) -> Result<T, ParseIntError>;
+type ParsePrefix<T> = fn(&[u8]) -> Option<(T, usize)>;
+
+/// 一种实现：`(名字, parse, try_parse, parse_prefix)`
+type Backend<T> = (&'static str, Parse<T>, TryParse<T>, ParsePrefix<T>);
+
+/// 一种类型的所有实现
+struct Backends<T> {
+    parsers: Vec<Backend<T>>,
+}
+
+macro_rules! backends {
+    ($t:ty, $parse:ident, $try_parse:ident, $parse_prefix:ident) => {{
+        #[allow(unused_mut)]
+        let mut parsers: Vec<Backend<$t>> = vec![
+            (
+                "normal",
+                normal::$parse,
+                normal::$try_parse,
+                normal::$parse_prefix,
+            ),
+            (
+                "dispatch",
+                num_parse::parse::<$t>,
+                num_parse::try_parse::<$t>,
+                num_parse::parse_prefix::<$t>,
+            ),
+        ];
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl") {
+            parsers.push((
+                "avx",
+                |s| unsafe { avx::$parse(s) },
+                |s| unsafe { avx::$try_parse(s) },
+                |bytes| unsafe { avx::$parse_prefix(bytes) },
+            ));
+        }
+        Backends { parsers }
+    }};
+}
+
+fn std_kind(kind: &num::IntErrorKind) -> IntErrorKind {
+    match kind {
+        num::IntErrorKind::Empty => IntErrorKind::Empty,
+        num::IntErrorKind::InvalidDigit => IntErrorKind::InvalidDigit,
+        num::IntErrorKind::PosOverflow => IntErrorKind::PosOverflow,
+        num::IntErrorKind::NegOverflow => IntErrorKind::NegOverflow,
+        kind => panic!("unexpected kind {kind:?}"),
+    }
+}
+
+/// 开头的符号的长度，无符号数只能有`+`
+fn sign_len(s: &str, signed: bool) -> usize {
+    match s.as_bytes().first() {
+        Some(b'+') => 1,
+        Some(b'-') if signed => 1,
+        _ => 0,
+    }
+}
+
+/// 第一个非数字字节的位置：跳过开头的符号，只有符号时是符号之后
+fn first_nondigit(s: &str, signed: bool) -> usize {
+    let sign = sign_len(s, signed);
+    s.as_bytes()[sign..]
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .map_or(s.len(), |i| sign + i)
+}
+
+impl<T: FromStr<Err = num::ParseIntError> + PartialEq + Debug + Copy> Backends<T> {
+    fn check(&self, s: &str, signed: bool) {
+        let expected = s.parse::<T>();
+
+        // 前缀解析：符号和之后连续的数字交给 `str::parse`
+        let end = first_nondigit(s, signed);
+        let expected_prefix = (end > sign_len(s, signed))
+            .then(|| s[..end].parse::<T>().ok().map(|x| (x, end)))
+            .flatten();
+
+        for &(name, parse, try_parse, parse_prefix) in &self.parsers {
+            assert_eq!(
+                parse_prefix(s.as_bytes()),
+                expected_prefix,
+                "{name} parse_prefix {s:?}"
+            );
+
+            assert_eq!(parse(s), expected.clone().ok(), "{name} parse {s:?}");
+
+            match (&expected, try_parse(s)) {
+                (Ok(x), Ok(y)) => assert_eq!(*x, y, "{name} try_parse {s:?}"),
+                (Err(e), Err(err)) => {
+                    assert_eq!(err.kind(), std_kind(e.kind()), "{name} try_parse {s:?}");
+                    let offset = (err.kind() == IntErrorKind::InvalidDigit)
+                        .then(|| first_nondigit(s, signed));
+                    assert_eq!(err.offset(), offset, "{name} try_parse {s:?}");
+                }
+                (x, y) => panic!("{name} try_parse {s:?}: expected {x:?}, got {y:?}"),
+            }
+        }
+    }
+}
+
+struct All {
+    u8: Backends<u8>,
+    u16: Backends<u16>,
+    u32: Backends<u32>,
+    u64: Backends<u64>,
+    u128: Backends<u128>,
+    i8: Backends<i8>,
+    i16: Backends<i16>,
+    i32: Backends<i32>,
+    i64: Backends<i64>,
+}
+
+impl All {
+    fn new() -> Self {
+        Self {
+            u8: backends!(u8, parse_u8, try_parse_u8, parse_u8_prefix),
+            u16: backends!(u16, parse_u16, try_parse_u16, parse_u16_prefix),
+            u32: backends!(u32, parse_u32, try_parse_u32, parse_u32_prefix),
+            u64: backends!(u64, parse_u64, try_parse_u64, parse_u64_prefix),
+            u128: backends!(u128, parse_u128, try_parse_u128, parse_u128_prefix),
+            i8: backends!(i8, parse_i8, try_parse_i8, parse_i8_prefix),
+            i16: backends!(i16, parse_i16, try_parse_i16, parse_i16_prefix),
+            i32: backends!(i32, parse_i32, try_parse_i32, parse_i32_prefix),
+            i64: backends!(i64, parse_i64, try_parse_i64, parse_i64_prefix),
+        }
+    }
+
+    fn check(&self, s: &str) {
+        self.u8.check(s, false);
+        self.u16.check(s, false);
+        self.u32.check(s, false);
+        self.u64.check(s, false);
+        self.u128.check(s, false);
+        self.i8.check(s, true);
+        self.i16.check(s, true);
+        self.i32.check(s, true);
+        self.i64.check(s, true);
+    }
+}
+
+/// 各类型的边界值附近
+fn boundaries() -> Vec<String> {
+    let mut values: Vec<i128> = vec![0, 1, 9, 10, 99, 100];
+    for max in [
+        u8::MAX as i128,
+        u16::MAX as i128,
+        u32::MAX as i128,
+        u64::MAX as i128,
+        i8::MAX as i128,
+        i16::MAX as i128,
+        i32::MAX as i128,
+        i64::MAX as i128,
+    ] {
+        for d in -2..=2 {
+            values.push(max + d);
+            values.push(-(max + d));
+        }
+    }
+    for p in 0..=38 {
+        values.push(10_i128.pow(p));
+        values.push(10_i128.pow(p) - 1);
+    }
+
+    let mut strings: Vec<String> = values.iter().map(i128::to_string).collect();
+    for max in [u128::MAX - 1, u128::MAX] {
+        strings.push(max.to_string());
+    }
+    strings.push("340282366920938463463374607431768211456".to_string());
+    strings.push("1".repeat(40));
+    strings
+}
+
+#[test]
+fn edge_cases() {
+    let all = All::new();
+    let cases = [
+        "", "+", "-", "++", "--", "+-", "-+", "+-1", "-+1", "0", "+0", "-0", "00", " 1", "1 ", " ",
+        "1_000", "0x10", "1e3", "1.0", "١", "½", "é", "1é", "\0", "9\u{0}", ":", "/", "+a", "-a",
+    ];
+    for s in cases {
+        all.check(s);
+    }
+}
+
+#[test]
+fn boundaries_with_signs_and_padding() {
+    let all = All::new();
+    for value in boundaries() {
+        let (sign, digits) = match value.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", value.as_str()),
+        };
+        for zeros in [0, 1, 2, 5, 10, 19, 20, 21, 31, 32, 33, 40, 64, 100] {
+            let padded = format!("{}{digits}", "0".repeat(zeros));
+            all.check(&format!("{sign}{padded}"));
+            if sign.is_empty() {
+                all.check(&format!("+{padded}"));
+            }
+            all.check(&format!("{sign}{padded}x"));
+            all.check(&format!("{sign}{padded}0"));
+        }
+    }
+}
+
+#[test]
+fn long_zero_padded() {
+    let all = All::new();
+    for len in 0..=200 {
+        let zeros = "0".repeat(len);
+        all.check(&zeros);
+        all.check(&format!("{zeros}1"));
+        all.check(&format!("+{zeros}7"));
+        all.check(&format!("-{zeros}7"));
+        all.check(&format!("{zeros}a"));
+    }
+}
+
+#[test]
+fn exhaustive_short_strings() {
+    const ALPHABET: &[u8] = b"0159+- a/:";
+
+    let all = All::new();
+    let mut s = Vec::new();
+    for len in 0..=4 {
+        let mut idx = vec![0; len];
+        loop {
+            s.clear();
+            s.extend(idx.iter().map(|&i| ALPHABET[i]));
+            all.check(std::str::from_utf8(&s).unwrap());
+
+            // 按进位的方式枚举下一个字符串
+            let Some(pos) = idx.iter().rposition(|&i| i + 1 < ALPHABET.len()) else {
+                break;
+            };
+            idx[pos] += 1;
+            idx[pos + 1..].fill(0);
+        }
+    }
+}
+
+#[test]
+fn exhaustive_small_types() {
+    let all = All::new();
+    for x in i16::MIN as i32..=u16::MAX as i32 {
+        all.u8.check(&x.to_string(), false);
+        all.u16.check(&x.to_string(), false);
+        all.i8.check(&x.to_string(), true);
+        all.i16.check(&x.to_string(), true);
+        all.i16.check(&format!("{x:+06}"), true);
+        all.u16.check(&format!("{x:+06}"), false);
+    }
+}
+
+#[test]
+fn random() {
+    const NOISE: &[u8] = b"0123456789+- a/:";
+
+    let all = All::new();
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for _ in 0..100_000 {
+        let value = rng.gen::<u128>() >> rng.gen_range(0..128);
+        let mut s = if rng.gen() {
+            value.to_string()
+        } else {
+            format!("-{value}")
+        };
+
+        for _ in 0..rng.gen_range(0..3) {
+            match rng.gen_range(0..5) {
+                0 => s.insert(0, '+'),
+                1 => {
+                    let i = rng.gen_range(0..=s.len());
+                    s.insert(i, *NOISE.choose(&mut rng).unwrap() as char);
+                }
+                2 if s.len() > 1 => {
+                    s.remove(rng.gen_range(0..s.len()));
+                }
+                3 => {
+                    let zeros = "0".repeat(rng.gen_range(1..40));
+                    let i = usize::from(s.starts_with(['+', '-']));
+                    s.insert_str(i, &zeros);
+                }
+                _ => {}
+            }
+        }
+        all.check(&s);
+    }
+}
+
+#[test]
+fn prefix_fields() {
+    let all = All::new();
+    for field in [
+        "0",
+        "42",
+        "-42",
+        "+42",
+        "255",
+        "256",
+        "-128",
+        "18446744073709551615",
+    ] {
+        for delimiter in [",", " ", "\n", "\t", ",,", "x1", "-1", "+1", ".5", "é"] {
+            all.check(&format!("{field}{delimiter}"));
+            all.check(&format!("{field}{delimiter}{field}"));
+            all.check(&format!("{}{field}{delimiter}", "0".repeat(40)));
+        }
+    }
+}
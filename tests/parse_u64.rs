@@ -0,0 +1,48 @@
+//! `num_parse::avx::parse_u64` 的回归测试
+
+/// 不超过8位时只用最低的一组，17~20位时高位的组决定是否溢出
+const CASES: [&str; 14] = [
+    "0",
+    "1",
+    "12345678",
+    "123456789",
+    "9999999999999999",
+    "99999999999999999",
+    "1844674407370955161",
+    "9999999999999999999",
+    "18440000000000000000",
+    "18446744073709551615",
+    "18446744073709551616",
+    "18447000000000000000",
+    "18450000000000000000",
+    "99999999999999999999",
+];
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+#[test]
+fn avx_parse_u64() {
+    use simd_demo::num_parse::avx::parse_u64;
+
+    if !(is_x86_feature_detected!("avx512bw") && is_x86_feature_detected!("avx512vl")) {
+        return;
+    }
+    for s in CASES {
+        assert_eq!(unsafe { parse_u64(s) }, s.parse().ok(), "{s}");
+    }
+}
+
+#[test]
+fn normal_parse_u64() {
+    use simd_demo::num_parse::normal::parse_u64;
+
+    for s in CASES {
+        assert_eq!(parse_u64(s), s.parse().ok(), "{s}");
+    }
+}
+
+#[test]
+fn dispatch_parse_u64() {
+    for s in CASES {
+        assert_eq!(simd_demo::num_parse::parse_u64(s), s.parse().ok(), "{s}");
+    }
+}
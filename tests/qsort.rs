@@ -0,0 +1,770 @@
+//! `qsort` 的各实现与 `normal::qsort`、`sort_unstable` 的差分测试
+
+use std::{cmp::Reverse, fmt::Debug};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "avx512")]
+use simd_demo::qsort::avx;
+use simd_demo::qsort::{self, avx2, normal, radix, ParallelConfig, SortElement};
+
+#[cfg(feature = "avx512")]
+use avx::SimdElement as AvxElement;
+
+/// 没有AVX-512实现时不限制
+#[cfg(not(feature = "avx512"))]
+trait AvxElement {}
+
+#[cfg(not(feature = "avx512"))]
+impl<T> AvxElement for T {}
+
+/// 测试用的元素类型：类型的极值、与填充相同的值，以及随机的值
+trait Element: SortElement + AvxElement + Debug + Send + 'static {
+    /// 排在最前和最后的值，也是AVX-512实现填充用的值
+    const MIN_VALUE: Self;
+    const MAX_VALUE: Self;
+
+    /// 其他容易出错的值，如无符号数中超过有符号数最大值的
+    const SPECIAL: &'static [Self];
+
+    fn from_i32(x: i32) -> Self;
+
+    /// 随机的值，浮点数覆盖所有不是NaN的位模式
+    fn random(rng: &mut StdRng) -> Self;
+}
+
+macro_rules! impl_int_element {
+    ($($t:ty: [$($special:expr),*]),*) => {$(
+        impl Element for $t {
+            const MIN_VALUE: Self = <$t>::MIN;
+            const MAX_VALUE: Self = <$t>::MAX;
+            const SPECIAL: &'static [Self] = &[$($special),*];
+
+            fn from_i32(x: i32) -> Self {
+                x as $t
+            }
+
+            fn random(rng: &mut StdRng) -> Self {
+                rng.gen()
+            }
+        }
+    )*};
+}
+
+impl_int_element! {
+    i32: [i32::MIN + 1, i32::MAX - 1, -1, 0],
+    i64: [i64::MIN + 1, i64::MAX - 1, i32::MIN as i64, i32::MAX as i64, -1, 0],
+    u32: [0, 1, i32::MAX as u32, i32::MAX as u32 + 1, u32::MAX - 1],
+    u64: [0, 1, i64::MAX as u64, i64::MAX as u64 + 1, u32::MAX as u64 + 1, u64::MAX - 1]
+}
+
+macro_rules! impl_float_element {
+    ($($t:ident),*) => {$(
+        impl Element for $t {
+            const MIN_VALUE: Self = $t::NEG_INFINITY;
+            const MAX_VALUE: Self = $t::INFINITY;
+            const SPECIAL: &'static [Self] = &[
+                $t::MIN,
+                $t::MAX,
+                -0.0,
+                0.0,
+                $t::MIN_POSITIVE,
+                -$t::MIN_POSITIVE,
+                $t::from_bits(1),
+            ];
+
+            fn from_i32(x: i32) -> Self {
+                x as $t
+            }
+
+            fn random(rng: &mut StdRng) -> Self {
+                let x = $t::from_bits(rng.gen());
+                if x.is_nan() {
+                    0.0
+                } else {
+                    x
+                }
+            }
+        }
+    )*};
+}
+
+impl_float_element!(f32, f64);
+
+/// 测试用的各种分布：随机、大量重复、类型的极值和特殊值、有序、逆序、全部相等
+fn inputs<T: Element>(rng: &mut StdRng, len: usize) -> Vec<Vec<T>> {
+    let len_i32 = len as i32;
+    vec![
+        (0..len).map(|_| T::random(rng)).collect(),
+        (0..len).map(|_| T::from_i32(rng.gen_range(0..4))).collect(),
+        (0..len)
+            .map(|_| match rng.gen_range(0..6) {
+                0 => T::MIN_VALUE,
+                1 => T::MAX_VALUE,
+                2 => T::SPECIAL[rng.gen_range(0..T::SPECIAL.len())],
+                _ => T::from_i32(rng.gen_range(-2..=2)),
+            })
+            .collect(),
+        (0..len_i32).map(T::from_i32).collect(),
+        (0..len_i32).rev().map(T::from_i32).collect(),
+        vec![T::MAX_VALUE; len],
+        vec![T::MIN_VALUE; len],
+    ]
+}
+
+/// `sort_unstable_by`的结果，同时检查 `normal::qsort` 与它相同
+fn sorted<T: Element>(arr: &[T]) -> Vec<T> {
+    let mut expected = arr.to_vec();
+    expected.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut by_normal = arr.to_vec();
+    normal::qsort(&mut by_normal);
+    assert_eq!(by_normal, expected, "normal len {}", arr.len());
+    expected
+}
+
+/// `sort`的结果与已经排好的`expected`相同
+fn check_sorted<T: Element>(name: &str, arr: &[T], expected: &[T], sort: impl Fn(&mut [T])) {
+    let mut actual = arr.to_vec();
+    sort(&mut actual);
+    assert_eq!(actual, expected, "{name} len {}", arr.len());
+}
+
+/// `sort`的结果与 `normal::qsort`、`sort_unstable_by` 相同
+fn check<T: Element>(name: &str, arr: &[T], sort: impl Fn(&mut [T])) {
+    check_sorted(name, arr, &sorted(arr), sort);
+}
+
+/// `T`的所有分布，`sort`、`par_sort` 以及AVX-512实现的结果都相同
+fn check_all_backends<T: Element>(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for len in (0..=300).chain([1000, 4096, 4097, 30_000]) {
+        for arr in inputs::<T>(&mut rng, len) {
+            let expected = sorted(&arr);
+            check_sorted("sort", &arr, &expected, qsort::sort);
+            check_sorted("par_sort", &arr, &expected, qsort::par_sort);
+            #[cfg(feature = "avx512")]
+            if is_x86_feature_detected!("avx512f") {
+                check_sorted("avx::qsort", &arr, &expected, |arr| unsafe {
+                    avx::qsort(arr)
+                });
+            }
+        }
+    }
+}
+
+/// 降序排序的结果与 `sort_unstable_by` 反过来比较相同；`T::MIN_VALUE` 是 `Reverse<T>` 填充用的值
+fn check_desc<T: Element>(seed: u64)
+where
+    Reverse<T>: SortElement + AvxElement,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    for len in (0..=300).chain([1000, 4096, 4097, 30_000]) {
+        for arr in inputs::<T>(&mut rng, len) {
+            let mut expected = arr.clone();
+            expected.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
+            check_sorted("sort_desc", &arr, &expected, qsort::sort_desc);
+            check_sorted("normal::qsort_desc", &arr, &expected, normal::qsort_desc);
+            #[cfg(feature = "avx512")]
+            if is_x86_feature_detected!("avx512f") {
+                check_sorted("avx::qsort_desc", &arr, &expected, |arr| unsafe {
+                    avx::qsort_desc(arr)
+                });
+            }
+        }
+    }
+}
+
+/// 对每种元素类型各生成一个测试
+macro_rules! per_type_tests {
+    ($check:ident: $($name:ident: $t:ty = $seed:expr),*) => {$(
+        #[test]
+        fn $name() {
+            $check::<$t>($seed);
+        }
+    )*};
+}
+
+per_type_tests! {
+    check_all_backends:
+    sort_i32: i32 = 0x132,
+    sort_u32: u32 = 0x032,
+    sort_i64: i64 = 0x164,
+    sort_u64: u64 = 0x064,
+    sort_f32: f32 = 0xf32,
+    sort_f64: f64 = 0xf64
+}
+
+per_type_tests! {
+    check_desc:
+    sort_desc_i32: i32 = 0xd132,
+    sort_desc_u32: u32 = 0xd032,
+    sort_desc_i64: i64 = 0xd164,
+    sort_desc_u64: u64 = 0xd064,
+    sort_desc_f32: f32 = 0xdf32,
+    sort_desc_f64: f64 = 0xdf64
+}
+
+#[test]
+fn avx2_qsort() {
+    if !is_x86_feature_detected!("avx2") {
+        return;
+    }
+
+    let mut rng = StdRng::seed_from_u64(0xa2);
+    for len in (0..=300).chain([1000, 4096, 4097, 100_000, 300_001]) {
+        for arr in inputs::<i32>(&mut rng, len) {
+            check("avx2", &arr, |arr| unsafe { avx2::qsort(arr) });
+        }
+    }
+}
+
+#[test]
+fn mostly_sorted() {
+    let mut rng = StdRng::seed_from_u64(0x5047);
+    for len in (0..=300_usize).chain([1000, 4097, 100_000]) {
+        // 开头至少一半有序（或逆序），剩下的随机，排序时只排剩下的部分再合并
+        for sorted in [len / 2, len * 3 / 4, len.saturating_sub(1)] {
+            let tail = len - sorted;
+            let mut arr: Vec<i32> = (0..sorted as i32).collect();
+            arr.extend((0..tail).map(|_| rng.gen_range(-10..sorted as i32 + 10)));
+            check("sort", &arr, qsort::sort);
+            arr[..sorted].reverse();
+            check("sort", &arr, qsort::sort);
+        }
+    }
+}
+
+#[test]
+fn par_sort_configs() {
+    let mut rng = StdRng::seed_from_u64(0x9a2);
+    // 顺序排序的阈值最小是256，线程数0当作1
+    let cutoffs = [0, 1, 255, 256, 257, 1000, 1 << 16];
+    for len in [0, 1, 255, 256, 257, 1000, 4097, 30_000] {
+        for arr in inputs::<i32>(&mut rng, len) {
+            let expected = sorted(&arr);
+            for threads in [0, 1, 2, 3, 8] {
+                for sequential_cutoff in cutoffs {
+                    let config = ParallelConfig {
+                        threads,
+                        sequential_cutoff,
+                    };
+                    check_sorted("par_sort_with", &arr, &expected, |arr| {
+                        qsort::par_sort_with(arr, &config)
+                    });
+                    check_sorted("normal::par_qsort", &arr, &expected, |arr| {
+                        normal::par_qsort(arr, &config)
+                    });
+                    #[cfg(feature = "avx512")]
+                    if is_x86_feature_detected!("avx512f") {
+                        check_sorted("avx::par_qsort", &arr, &expected, |arr| unsafe {
+                            avx::par_qsort(arr, &config)
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "avx512")]
+#[test]
+fn avx_qsort_cutoffs() {
+    if !is_x86_feature_detected!("avx512f") {
+        return;
+    }
+
+    // 限制到 2..=16 个向量的两端附近：32位元素为 32..=256，64位元素为 16..=128
+    let cutoffs = [
+        0,
+        1,
+        15,
+        16,
+        17,
+        31,
+        32,
+        33,
+        63,
+        64,
+        65,
+        127,
+        128,
+        129,
+        255,
+        256,
+        257,
+        1000,
+        usize::MAX,
+    ];
+    let mut rng = StdRng::seed_from_u64(0xc07);
+    for len in (0..=300).chain([511, 512, 513, 4097, 30_000]) {
+        for arr in inputs::<i32>(&mut rng, len) {
+            let expected = sorted(&arr);
+            let wide: Vec<i64> = arr
+                .iter()
+                .map(|&x| match x {
+                    i32::MIN => i64::MIN,
+                    i32::MAX => i64::MAX,
+                    x => x as i64,
+                })
+                .collect();
+            let mut wide_expected = wide.clone();
+            wide_expected.sort_unstable();
+
+            for cutoff in cutoffs {
+                check_sorted("avx::qsort_with_cutoff", &arr, &expected, |arr| unsafe {
+                    avx::qsort_with_cutoff(arr, cutoff)
+                });
+
+                let mut actual = wide.clone();
+                unsafe { avx::qsort_with_cutoff(&mut actual, cutoff) };
+                assert_eq!(actual, wide_expected, "i64 cutoff {cutoff} len {len}");
+            }
+        }
+    }
+}
+
+/// 一种实现的 `sort_pairs` 与 `argsort`
+type PairsBackend = (
+    &'static str,
+    fn(&mut [i32], &mut [u32]),
+    fn(&[i32]) -> Vec<u32>,
+);
+
+#[test]
+fn sort_pairs() {
+    #[allow(unused_mut)]
+    let mut backends: Vec<PairsBackend> = vec![
+        ("normal", normal::sort_pairs, normal::argsort),
+        ("dispatch", qsort::sort_pairs, qsort::argsort),
+    ];
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512f") {
+        backends.push((
+            "avx",
+            |keys, values| unsafe { avx::sort_pairs(keys, values) },
+            |keys| unsafe { avx::argsort(keys) },
+        ));
+    }
+
+    let mut rng = StdRng::seed_from_u64(0x9a1);
+    for len in (0..=300).chain([1000, 4097, 100_000]) {
+        let mut arrs = inputs::<i32>(&mut rng, len);
+        // 大部分是和填充相同的`i32::MAX`
+        arrs.push(
+            (0..len)
+                .map(|_| {
+                    if rng.gen_ratio(9, 10) {
+                        i32::MAX
+                    } else {
+                        rng.gen()
+                    }
+                })
+                .collect(),
+        );
+        for keys in arrs {
+            let expected = sorted(&keys);
+            for (name, sort_pairs, argsort) in &backends {
+                // payload是原来的下标，每一对都要保持不变
+                let mut actual = keys.clone();
+                let mut values: Vec<u32> = (0..len as u32).collect();
+                sort_pairs(&mut actual, &mut values);
+                assert_eq!(actual, expected, "{name} keys len {len}");
+                let mut seen = vec![false; len];
+                for (&key, &i) in actual.iter().zip(&values) {
+                    assert_eq!(keys[i as usize], key, "{name} pair len {len}");
+                    assert!(!seen[i as usize], "{name} payload {i} twice, len {len}");
+                    seen[i as usize] = true;
+                }
+
+                let indices = argsort(&keys);
+                let by_index: Vec<i32> = indices.iter().map(|&i| keys[i as usize]).collect();
+                assert_eq!(by_index, expected, "{name} argsort len {len}");
+            }
+        }
+    }
+}
+
+/// 按key排序的元素，带着不能复制的payload
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Item {
+    key: i32,
+    name: String,
+}
+
+type SortByKey = fn(&mut [Item], fn(&Item) -> i32);
+
+#[test]
+fn sort_by_key() {
+    #[allow(unused_mut)]
+    let mut backends: Vec<(&str, SortByKey)> = vec![
+        ("normal", normal::sort_by_key),
+        ("dispatch", qsort::sort_by_key),
+    ];
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512f") {
+        backends.push(("avx", |arr, key| unsafe { avx::sort_by_key(arr, key) }));
+    }
+
+    let mut rng = StdRng::seed_from_u64(0xb4);
+    // 跨过 `sort_pairs` 直接用双调排序网络的长度（128）
+    for len in (0..=300).chain([1000, 10_000]) {
+        for keys in inputs::<i32>(&mut rng, len) {
+            let items: Vec<Item> = keys
+                .iter()
+                .enumerate()
+                .map(|(i, &key)| Item {
+                    key,
+                    name: i.to_string(),
+                })
+                .collect();
+            let mut expected = items.clone();
+            expected.sort_by_key(|item| item.key);
+            let expected_keys: Vec<i32> = expected.iter().map(|item| item.key).collect();
+            expected.sort_unstable();
+
+            for (name, sort_by_key) in &backends {
+                let mut actual = items.clone();
+                sort_by_key(&mut actual, |item| item.key);
+                let actual_keys: Vec<i32> = actual.iter().map(|item| item.key).collect();
+                assert_eq!(actual_keys, expected_keys, "{name} keys len {len}");
+                // 不稳定排序，元素的多重集合相同
+                actual.sort_unstable();
+                assert_eq!(actual, expected, "{name} items len {len}");
+            }
+        }
+    }
+}
+
+macro_rules! total_order_tests {
+    ($($name:ident: $t:ident, $bits:ty = $seed:expr),*) => {$(
+        /// 与 `sort_by(total_cmp)` 按位比较：NaN的符号和payload、-0.0与+0.0都要分开
+        #[test]
+        fn $name() {
+            let special = [
+                $t::NAN,
+                -$t::NAN,
+                $t::from_bits($t::NAN.to_bits() | 1),
+                -$t::from_bits($t::NAN.to_bits() | 1),
+                $t::from_bits(<$bits>::MAX),
+                $t::INFINITY,
+                $t::NEG_INFINITY,
+                0.0,
+                -0.0,
+                $t::MIN,
+                $t::MAX,
+                $t::MIN_POSITIVE,
+                -$t::MIN_POSITIVE,
+                $t::from_bits(1),
+                -$t::from_bits(1),
+                $t::MIN_POSITIVE / 2.0,
+                1.0,
+                -1.0,
+            ];
+
+            let mut rng = StdRng::seed_from_u64($seed);
+            for len in (0..=300).chain([1000, 4097, 30_000]) {
+                let arrs: [Vec<$t>; 3] = [
+                    (0..len).map(|_| $t::from_bits(rng.gen())).collect(),
+                    (0..len)
+                        .map(|_| special[rng.gen_range(0..special.len())])
+                        .collect(),
+                    (0..len)
+                        .map(|_| match rng.gen_range(0..4) {
+                            0 => special[rng.gen_range(0..special.len())],
+                            _ => rng.gen_range(-2.0..2.0),
+                        })
+                        .collect(),
+                ];
+                for arr in arrs {
+                    let mut expected = arr.clone();
+                    expected.sort_by($t::total_cmp);
+                    let expected: Vec<$bits> = expected.iter().map(|x| x.to_bits()).collect();
+
+                    let check = |name: &str, sort: &dyn Fn(&mut [$t])| {
+                        let mut actual = arr.clone();
+                        sort(&mut actual);
+                        let actual: Vec<$bits> = actual.iter().map(|x| x.to_bits()).collect();
+                        assert_eq!(actual, expected, "{name} len {len}");
+                    };
+                    check("normal::qsort_total", &normal::qsort_total);
+                    check("sort_total", &qsort::sort_total);
+                    #[cfg(feature = "avx512")]
+                    if is_x86_feature_detected!("avx512f") {
+                        check("avx::qsort_total", &|arr| unsafe { avx::qsort_total(arr) });
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+total_order_tests! {
+    total_order_f32: f32, u32 = 0x7f32,
+    total_order_f64: f64, u64 = 0x7f64
+}
+
+type Select<T> = fn(&mut [T], usize);
+
+/// `select_nth_unstable`和`partial_sort`的结果与排好的数组一致，`k`取两端、中间和超出范围的值
+fn check_select<T: Element>(seed: u64) {
+    #[allow(unused_mut)]
+    let mut selects: Vec<(&str, Select<T>, Select<T>)> = vec![
+        (
+            "normal",
+            |arr, k| {
+                normal::select_nth_unstable(arr, k);
+            },
+            normal::partial_sort,
+        ),
+        (
+            "dispatch",
+            |arr, k| {
+                qsort::select_nth_unstable(arr, k);
+            },
+            qsort::partial_sort,
+        ),
+    ];
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512f") {
+        selects.push((
+            "avx",
+            |arr, k| unsafe {
+                avx::select_nth_unstable(arr, k);
+            },
+            |arr, k| unsafe { avx::partial_sort(arr, k) },
+        ));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    for len in (0..=100).chain([255, 256, 257, 1000, 4097]) {
+        for arr in inputs::<T>(&mut rng, len) {
+            let expected = sorted(&arr);
+            let mut ks = vec![0, 1, len / 2, len.saturating_sub(1), len, len + 5];
+            if len > 0 {
+                ks.push(rng.gen_range(0..len));
+            }
+            for (name, select, partial_sort) in &selects {
+                for &k in &ks {
+                    if k < len {
+                        let mut actual = arr.clone();
+                        select(&mut actual, k);
+                        let nth = actual[k];
+                        assert_eq!(nth, expected[k], "{name} nth k {k} len {len}");
+                        assert!(actual[..k].iter().all(|&x| x <= nth), "{name} left k {k}");
+                        assert!(
+                            actual[k + 1..].iter().all(|&x| x >= nth),
+                            "{name} right k {k}"
+                        );
+                        check_sorted(name, &actual, &expected, |arr| normal::qsort(arr));
+                    }
+
+                    let mut actual = arr.clone();
+                    partial_sort(&mut actual, k);
+                    let prefix = k.min(len);
+                    assert_eq!(
+                        actual[..prefix],
+                        expected[..prefix],
+                        "{name} k {k} len {len}"
+                    );
+                    check_sorted(name, &actual, &expected, |arr| normal::qsort(arr));
+                }
+            }
+        }
+    }
+}
+
+per_type_tests! {
+    check_select:
+    select_i32: i32 = 0x5132,
+    select_u64: u64 = 0x5064,
+    select_f32: f32 = 0x5f32,
+    select_f64: f64 = 0x5f64
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn select_out_of_range() {
+    qsort::select_nth_unstable(&mut [3, 1, 2], 3);
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn normal_select_out_of_range() {
+    normal::select_nth_unstable(&mut [3.0, 1.0], 2);
+}
+
+/// 划分的结果：`arr[..n]`正好是满足`pred`的元素，`arr[n..]`是其余的，与 `Iterator::partition` 比较
+fn check_partition<T: Element>(
+    name: &str,
+    arr: &[T],
+    actual: &[T],
+    n: usize,
+    pred: impl Fn(T) -> bool,
+) {
+    let sort = |arr: &[T]| {
+        let mut arr = arr.to_vec();
+        arr.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        arr
+    };
+    let (left, right): (Vec<T>, Vec<T>) = arr.iter().partition(|&&x| pred(x));
+    assert_eq!(n, left.len(), "{name} split len {}", arr.len());
+    assert_eq!(
+        sort(&actual[..n]),
+        sort(&left),
+        "{name} left len {}",
+        arr.len()
+    );
+    assert_eq!(
+        sort(&actual[n..]),
+        sort(&right),
+        "{name} right len {}",
+        arr.len()
+    );
+}
+
+type Partition<T> = fn(&mut [T], T, T) -> usize;
+
+/// 一种实现的 `partition_le`、`partition_lt`、`partition_between`
+type PartitionBackend<T> = (&'static str, Partition<T>, Partition<T>, Partition<T>);
+
+/// `partition_le`、`partition_lt`、`partition_between`，长度在两个向量附近，枢轴取数组中的值和极值
+fn check_partitions<T: Element>(seed: u64) {
+    // 单个枢轴的划分忽略第二个参数
+    #[allow(unused_mut)]
+    let mut partitions: Vec<PartitionBackend<T>> = vec![
+        (
+            "normal",
+            |arr, pivot, _| normal::partition_le(arr, pivot),
+            |arr, pivot, _| normal::partition_lt(arr, pivot),
+            normal::partition_between,
+        ),
+        (
+            "dispatch",
+            |arr, pivot, _| qsort::partition_le(arr, pivot),
+            |arr, pivot, _| qsort::partition_lt(arr, pivot),
+            qsort::partition_between,
+        ),
+    ];
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512f") {
+        partitions.push((
+            "avx",
+            |arr, pivot, _| unsafe { avx::partition_le(arr, pivot) },
+            |arr, pivot, _| unsafe { avx::partition_lt(arr, pivot) },
+            |arr, low, high| unsafe { avx::partition_between(arr, low, high) },
+        ));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    for len in (0..=70).chain([255, 256, 257, 1000]) {
+        for arr in inputs::<T>(&mut rng, len) {
+            let mut pivots = vec![T::MIN_VALUE, T::MAX_VALUE, T::from_i32(0)];
+            pivots.extend(
+                (0..3)
+                    .filter(|_| len > 0)
+                    .map(|_| arr[rng.gen_range(0..len)]),
+            );
+            for (name, le, lt, between) in &partitions {
+                for &low in &pivots {
+                    let mut actual = arr.clone();
+                    let n = le(&mut actual, low, low);
+                    check_partition(name, &arr, &actual, n, |x| x <= low);
+
+                    let mut actual = arr.clone();
+                    let n = lt(&mut actual, low, low);
+                    check_partition(name, &arr, &actual, n, |x| x < low);
+
+                    for &high in &pivots {
+                        let mut actual = arr.clone();
+                        let n = between(&mut actual, low, high);
+                        check_partition(name, &arr, &actual, n, |x| low <= x && x <= high);
+                    }
+                }
+            }
+        }
+    }
+}
+
+per_type_tests! {
+    check_partitions:
+    partition_i32: i32 = 0x9132,
+    partition_u32: u32 = 0x9032,
+    partition_i64: i64 = 0x9164,
+    partition_f64: f64 = 0x9f64
+}
+
+#[cfg(feature = "avx512")]
+#[test]
+fn avx_partition_by_mask() {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{_mm512_cmpgt_epi32_mask, _mm512_setzero_si512};
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{_mm512_cmpgt_epi32_mask, _mm512_setzero_si512};
+
+    if !is_x86_feature_detected!("avx512f") {
+        return;
+    }
+
+    let mut rng = StdRng::seed_from_u64(0x3a5c);
+    for len in (0..=70).chain([1000]) {
+        for arr in inputs::<i32>(&mut rng, len) {
+            let mut actual = arr.clone();
+            let n = unsafe {
+                avx::partition_by_mask(
+                    &mut actual,
+                    |v| _mm512_cmpgt_epi32_mask(v, _mm512_setzero_si512()),
+                    |x| x > 0,
+                )
+            };
+            check_partition("avx::partition_by_mask", &arr, &actual, n, |x| x > 0);
+
+            // 8个分量时掩码的高位被忽略
+            let wide: Vec<i64> = arr.iter().map(|&x| x as i64).collect();
+            let mut actual = wide.clone();
+            let n = unsafe { avx::partition_by_mask(&mut actual, |_| u16::MAX, |_| true) };
+            check_partition("avx::partition_by_mask", &wide, &actual, n, |_| true);
+        }
+    }
+}
+
+macro_rules! radix_tests {
+    ($($name:ident: $t:ty = $seed:expr),*) => {$(
+        #[test]
+        fn $name() {
+            let mut rng = StdRng::seed_from_u64($seed);
+            for len in (0..=300).chain([1000, 4097, 30_000]) {
+                let mut arrs = inputs::<$t>(&mut rng, len);
+                // 第二个字节对所有元素都相同，这一趟跳过
+                arrs.push(
+                    (0..len)
+                        .map(|_| rng.gen::<$t>() & !((0xFF as $t) << 8) | ((0x5A as $t) << 8))
+                        .collect(),
+                );
+                // 只有最低字节不同，有符号数都是负数：只分一趟，结果要从缓冲区拷回来
+                arrs.push(
+                    (0..len)
+                        .map(|_| rng.gen::<u8>() as $t | !(0xFF as $t))
+                        .collect(),
+                );
+                for arr in arrs {
+                    let mut expected = arr.clone();
+                    expected.sort_unstable();
+                    check_sorted("radix_sort", &arr, &expected, qsort::radix_sort);
+                    check_sorted("radix::radix_sort", &arr, &expected, radix::radix_sort);
+                    #[cfg(feature = "avx512")]
+                    if is_x86_feature_detected!("avx512f") {
+                        check_sorted("radix::radix_sort_avx", &arr, &expected, |arr| unsafe {
+                            radix::radix_sort_avx(arr)
+                        });
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+radix_tests! {
+    radix_i32: i32 = 0x7a32,
+    radix_u32: u32 = 0x7a33,
+    radix_i64: i64 = 0x7a64,
+    radix_u64: u64 = 0x7a65
+}
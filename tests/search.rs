@@ -0,0 +1,189 @@
+//! `search` 的各实现与 `slice::partition_point` 的差分测试
+
+use std::ops::Range;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "avx512")]
+use simd_demo::search::avx;
+use simd_demo::search::{self, normal};
+
+type Bound = fn(&[i32], i32) -> usize;
+type Batch = fn(&[i32], &[i32], &mut [usize]);
+
+/// 一种实现的所有查找
+struct Backend {
+    name: &'static str,
+    lower_bound: Bound,
+    upper_bound: Bound,
+    contains: fn(&[i32], i32) -> bool,
+    equal_range: fn(&[i32], i32) -> Range<usize>,
+    lower_bound_batch: Batch,
+    upper_bound_batch: Batch,
+}
+
+fn backends() -> Vec<Backend> {
+    #[allow(unused_mut)]
+    let mut backends = vec![
+        Backend {
+            name: "normal",
+            lower_bound: normal::lower_bound,
+            upper_bound: normal::upper_bound,
+            contains: normal::contains,
+            equal_range: normal::equal_range,
+            lower_bound_batch: normal::lower_bound_batch,
+            upper_bound_batch: normal::upper_bound_batch,
+        },
+        Backend {
+            name: "dispatch",
+            lower_bound: search::lower_bound,
+            upper_bound: search::upper_bound,
+            contains: search::contains,
+            equal_range: search::equal_range,
+            lower_bound_batch: search::lower_bound_batch,
+            upper_bound_batch: search::upper_bound_batch,
+        },
+    ];
+    #[cfg(feature = "avx512")]
+    if is_x86_feature_detected!("avx512f") {
+        backends.push(Backend {
+            name: "avx",
+            lower_bound: |arr, key| unsafe { avx::lower_bound(arr, key) },
+            upper_bound: |arr, key| unsafe { avx::upper_bound(arr, key) },
+            contains: |arr, key| unsafe { avx::contains(arr, key) },
+            equal_range: |arr, key| unsafe { avx::equal_range(arr, key) },
+            lower_bound_batch: |arr, keys, out| unsafe { avx::lower_bound_batch(arr, keys, out) },
+            upper_bound_batch: |arr, keys, out| unsafe { avx::upper_bound_batch(arr, keys, out) },
+        });
+    }
+    backends
+}
+
+/// 有序的数组：随机、大量重复、含有类型的极值
+fn sorted_inputs(rng: &mut StdRng, len: usize) -> Vec<Vec<i32>> {
+    let mut arrs: Vec<Vec<i32>> = vec![
+        (0..len).map(|_| rng.gen_range(-1000..1000)).collect(),
+        (0..len).map(|_| rng.gen_range(0..4)).collect(),
+        (0..len)
+            .map(|_| match rng.gen_range(0..4) {
+                0 => i32::MIN,
+                1 => i32::MAX,
+                _ => rng.gen_range(-2..=2),
+            })
+            .collect(),
+        vec![7; len],
+    ];
+    for arr in &mut arrs {
+        arr.sort_unstable();
+    }
+    arrs
+}
+
+/// 数组中的值、它们的邻居、数组范围之外的值和类型的极值
+fn keys(arr: &[i32]) -> Vec<i32> {
+    let mut keys = vec![
+        i32::MIN,
+        i32::MIN + 1,
+        i32::MAX - 1,
+        i32::MAX,
+        -1001,
+        1001,
+        0,
+    ];
+    for &x in arr {
+        keys.extend([x.saturating_sub(1), x, x.saturating_add(1)]);
+    }
+    keys
+}
+
+fn check(backends: &[Backend], arr: &[i32]) {
+    let keys = keys(arr);
+    let lower: Vec<usize> = keys
+        .iter()
+        .map(|&k| arr.partition_point(|&x| x < k))
+        .collect();
+    let upper: Vec<usize> = keys
+        .iter()
+        .map(|&k| arr.partition_point(|&x| x <= k))
+        .collect();
+
+    for backend in backends {
+        let name = backend.name;
+        let len = arr.len();
+        for (i, &key) in keys.iter().enumerate() {
+            assert_eq!(
+                (backend.lower_bound)(arr, key),
+                lower[i],
+                "{name} lower {key} len {len}"
+            );
+            assert_eq!(
+                (backend.upper_bound)(arr, key),
+                upper[i],
+                "{name} upper {key} len {len}"
+            );
+            assert_eq!(
+                (backend.equal_range)(arr, key),
+                lower[i]..upper[i],
+                "{name} equal_range {key} len {len}"
+            );
+            assert_eq!(
+                (backend.contains)(arr, key),
+                lower[i] < upper[i],
+                "{name} contains {key} len {len}"
+            );
+        }
+
+        let mut out = vec![usize::MAX; keys.len()];
+        (backend.lower_bound_batch)(arr, &keys, &mut out);
+        assert_eq!(out, lower, "{name} lower_bound_batch len {len}");
+        (backend.upper_bound_batch)(arr, &keys, &mut out);
+        assert_eq!(out, upper, "{name} upper_bound_batch len {len}");
+    }
+}
+
+#[test]
+fn small_lengths() {
+    let backends = backends();
+    let mut rng = StdRng::seed_from_u64(0x5ea);
+    for len in 0..=64 {
+        for arr in sorted_inputs(&mut rng, len) {
+            check(&backends, &arr);
+        }
+    }
+}
+
+#[test]
+fn large_lengths() {
+    let backends = backends();
+    let mut rng = StdRng::seed_from_u64(0x5eb);
+    for len in [255, 256, 257, 1000, 4097] {
+        for arr in sorted_inputs(&mut rng, len) {
+            check(&backends, &arr);
+        }
+    }
+}
+
+#[test]
+fn batch_key_counts() {
+    let backends = backends();
+    let mut rng = StdRng::seed_from_u64(0x5ec);
+    let arr = &sorted_inputs(&mut rng, 1000)[0];
+    // 每次取16个key，剩下不满16个的逐个查找
+    for count in 0..=40 {
+        let keys: Vec<i32> = (0..count).map(|_| rng.gen_range(-1100..1100)).collect();
+        let lower: Vec<usize> = keys
+            .iter()
+            .map(|&k| arr.partition_point(|&x| x < k))
+            .collect();
+        for backend in &backends {
+            let mut out = vec![usize::MAX; count];
+            (backend.lower_bound_batch)(arr, &keys, &mut out);
+            assert_eq!(out, lower, "{} count {count}", backend.name);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn batch_wrong_output_len() {
+    search::lower_bound_batch(&[1, 2, 3], &[1, 2], &mut [0; 1]);
+}
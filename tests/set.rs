@@ -0,0 +1,235 @@
+//! `set` 的所有实现与 `set::normal` 的差分测试
+
+use std::fmt::Debug;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "avx512")]
+use simd_demo::set::avx;
+use simd_demo::set::{self, avx2, normal, SetElement};
+
+type SetOp<T> = fn(&[T], &[T]) -> Vec<T>;
+
+/// 一种实现的所有集合运算
+struct Backend<T> {
+    name: &'static str,
+    intersect: SetOp<T>,
+    intersect_count: fn(&[T], &[T]) -> usize,
+    union: SetOp<T>,
+    difference: SetOp<T>,
+    dedup: fn(&mut [T]) -> usize,
+}
+
+macro_rules! backends {
+    ($t:ty) => {{
+        let mut backends: Vec<Backend<$t>> = vec![
+            Backend {
+                name: "normal",
+                intersect: normal::intersect,
+                intersect_count: normal::intersect_count,
+                union: normal::union,
+                difference: normal::difference,
+                dedup: normal::dedup,
+            },
+            Backend {
+                name: "dispatch",
+                intersect: set::intersect,
+                intersect_count: set::intersect_count,
+                union: set::union,
+                difference: set::difference,
+                dedup: set::dedup,
+            },
+        ];
+        if is_x86_feature_detected!("avx2") {
+            backends.push(Backend {
+                name: "avx2",
+                intersect: |a, b| unsafe { avx2::intersect(a, b) },
+                intersect_count: |a, b| unsafe { avx2::intersect_count(a, b) },
+                union: |a, b| unsafe { avx2::union(a, b) },
+                difference: |a, b| unsafe { avx2::difference(a, b) },
+                dedup: |arr| unsafe { avx2::dedup(arr) },
+            });
+        }
+        #[cfg(feature = "avx512")]
+        if is_x86_feature_detected!("avx512f") {
+            backends.push(Backend {
+                name: "avx",
+                intersect: |a, b| unsafe { avx::intersect(a, b) },
+                intersect_count: |a, b| unsafe { avx::intersect_count(a, b) },
+                union: |a, b| unsafe { avx::union(a, b) },
+                difference: |a, b| unsafe { avx::difference(a, b) },
+                dedup: |arr| unsafe { avx::dedup(arr) },
+            });
+        }
+        backends
+    }};
+}
+
+/// 有序、无重复的输入：所有实现的结果与 `normal` 相同
+fn check_sets<T: SetElement + Debug>(backends: &[Backend<T>], a: &[T], b: &[T]) {
+    let intersect = normal::intersect(a, b);
+    let union = normal::union(a, b);
+    let difference = normal::difference(a, b);
+    for backend in backends {
+        let name = backend.name;
+        let (la, lb) = (a.len(), b.len());
+        assert_eq!(
+            (backend.intersect)(a, b),
+            intersect,
+            "{name} intersect {la}x{lb}"
+        );
+        assert_eq!(
+            (backend.intersect_count)(a, b),
+            intersect.len(),
+            "{name} intersect_count {la}x{lb}"
+        );
+        assert_eq!((backend.union)(a, b), union, "{name} union {la}x{lb}");
+        assert_eq!(
+            (backend.difference)(a, b),
+            difference,
+            "{name} difference {la}x{lb}"
+        );
+    }
+}
+
+/// 任意输入（可以无序、有重复）：结果不作保证，只检查不越界，并且交集、差集的每个元素都来自`a`
+fn check_unchecked<T: SetElement + Debug>(backends: &[Backend<T>], a: &[T], b: &[T]) {
+    for backend in backends {
+        let name = backend.name;
+        let intersect = (backend.intersect)(a, b);
+        assert!(intersect.len() <= a.len(), "{name} intersect");
+        assert!(intersect.iter().all(|x| a.contains(x)), "{name} intersect");
+        let difference = (backend.difference)(a, b);
+        assert!(difference.len() <= a.len(), "{name} difference");
+        assert!(
+            difference.iter().all(|x| a.contains(x)),
+            "{name} difference"
+        );
+        assert!(
+            (backend.union)(a, b).len() <= a.len() + b.len(),
+            "{name} union"
+        );
+        (backend.intersect_count)(a, b);
+    }
+}
+
+/// `dedup`只要求相邻的重复元素，任意输入的结果都与 `normal` 相同
+fn check_dedup<T: SetElement + Debug>(backends: &[Backend<T>], arr: &[T]) {
+    let mut expected = arr.to_vec();
+    let len = normal::dedup(&mut expected);
+    for backend in backends {
+        let mut arr = arr.to_vec();
+        let n = (backend.dedup)(&mut arr);
+        assert_eq!(&arr[..n], &expected[..len], "{} dedup", backend.name);
+    }
+}
+
+/// 向量块长度附近的长度
+const LENGTHS: [usize; 20] = [
+    0, 1, 2, 7, 8, 9, 15, 16, 17, 31, 32, 33, 47, 48, 49, 63, 64, 65, 100, 257,
+];
+
+/// 从 `values` 中随机取`len`个不同的元素，有序
+fn random_set<T: SetElement>(
+    rng: &mut StdRng,
+    len: usize,
+    values: impl Fn(&mut StdRng) -> T,
+) -> Vec<T> {
+    let mut set: Vec<T> = (0..len).map(|_| values(rng)).collect();
+    set.sort_unstable();
+    set.dedup();
+    set
+}
+
+macro_rules! set_tests {
+    ($($t:ident),*) => {$(
+        mod $t {
+            use super::*;
+
+            #[test]
+            fn edge_lengths() {
+                let backends = backends!($t);
+                let mut rng = StdRng::seed_from_u64(0x5e7);
+                for la in LENGTHS {
+                    for lb in LENGTHS {
+                        // 值域越小，两个集合相同的元素越多
+                        for range in [1, 2, 4, 64] {
+                            let max = ((la.max(lb) * range) as $t).max(1);
+                            let a = random_set(&mut rng, la, |rng| rng.gen_range(0..max));
+                            let b = random_set(&mut rng, lb, |rng| rng.gen_range(0..max));
+                            check_sets(&backends, &a, &b);
+                            check_sets(&backends, &b, &a);
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn extremes() {
+                let backends = backends!($t);
+                let mut rng = StdRng::seed_from_u64(0xed6e);
+                for len in LENGTHS {
+                    // 靠近类型的最小值、最大值
+                    let low = random_set(&mut rng, len, |rng| $t::MIN.wrapping_add(rng.gen_range(0..64)));
+                    let high = random_set(&mut rng, len, |rng| $t::MAX.wrapping_sub(rng.gen_range(0..64)));
+                    let both: Vec<$t> = low.iter().chain(&high).copied().collect();
+                    check_sets(&backends, &low, &high);
+                    check_sets(&backends, &both, &high);
+                    check_sets(&backends, &low, &both);
+                    check_sets(&backends, &both, &both);
+                }
+            }
+
+            #[test]
+            fn random() {
+                let backends = backends!($t);
+                let mut rng = StdRng::seed_from_u64(0x7a4d);
+                for _ in 0..300 {
+                    let la = rng.gen_range(0..3000);
+                    let lb = rng.gen_range(0..3000);
+                    let max = rng.gen_range(1..10_000) as $t;
+                    let a = random_set(&mut rng, la, |rng| rng.gen_range(0..max));
+                    let b = random_set(&mut rng, lb, |rng| rng.gen_range(0..max));
+                    check_sets(&backends, &a, &b);
+                }
+            }
+
+            #[test]
+            fn duplicates_and_unsorted() {
+                let backends = backends!($t);
+
+                let a: Vec<$t> = [5; 48].into_iter().chain([7; 16]).collect();
+                let b: Vec<$t> = [5; 15].into_iter().chain([6]).collect();
+                check_unchecked(&backends, &a, &b);
+                check_unchecked(&backends, &b, &a);
+
+                let mut rng = StdRng::seed_from_u64(0xd0b);
+                for _ in 0..300 {
+                    let max = rng.gen_range(1..8);
+                    let mut a: Vec<$t> = (0..rng.gen_range(0..300)).map(|_| rng.gen_range(0..max)).collect();
+                    let mut b: Vec<$t> = (0..rng.gen_range(0..300)).map(|_| rng.gen_range(0..max)).collect();
+                    check_unchecked(&backends, &a, &b);
+                    a.sort_unstable();
+                    b.sort_unstable();
+                    check_unchecked(&backends, &a, &b);
+                }
+            }
+
+            #[test]
+            fn dedup() {
+                let backends = backends!($t);
+                let mut rng = StdRng::seed_from_u64(0xdedd);
+                for len in (0..=300).chain([1000, 4097]) {
+                    for max in [1, 2, 8, 1000] {
+                        let mut arr: Vec<$t> = (0..len).map(|_| rng.gen_range(0..max)).collect();
+                        check_dedup(&backends, &arr);
+                        arr.sort_unstable();
+                        check_dedup(&backends, &arr);
+                    }
+                }
+                check_dedup(&backends, &[$t::MIN, $t::MIN, $t::MAX, $t::MAX]);
+            }
+        }
+    )*};
+}
+
+set_tests!(i32, u32);